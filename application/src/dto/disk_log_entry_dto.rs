@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct DiskLogEntryDto {
     pub timestamp: String,
     pub host: String,
@@ -9,4 +9,27 @@ pub struct DiskLogEntryDto {
     pub syslog_tag: String,
     pub source: String,
     pub message: String,
+    /// Common name of the client certificate the entry was received with. Only set by the
+    /// inputs authenticating their clients, so that entries can't claim an identity.
+    #[serde(skip_deserializing)]
+    pub client_identity: Option<String>,
+    #[serde(default)]
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub proc_id: Option<String>,
+    #[serde(default)]
+    pub msg_id: Option<String>,
+    #[serde(default)]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub trace_id: Option<String>,
+    #[serde(default)]
+    pub span_id: Option<String>,
+    /// Attributes of the entity which produced the entry, e.g. an OpenTelemetry resource.
+    #[serde(default)]
+    pub resource: serde_json::Map<String, serde_json::Value>,
+    /// Names of the redaction rules which rewrote the entry, e.g. `email`. Only set by the
+    /// redaction processor, so that clients can't mark their entries as redacted.
+    #[serde(skip_deserializing)]
+    pub redactions: Vec<String>,
 }
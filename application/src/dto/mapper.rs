@@ -11,6 +11,21 @@ impl From<LogEntry> for DiskLogEntryDto {
             syslog_tag: log.syslog_tag,
             source: log.source,
             message: log.message,
+            client_identity: log.client_identity,
+            app_name: log.app_name,
+            proc_id: log.proc_id,
+            msg_id: log.msg_id,
+            attributes: match log.attributes {
+                serde_json::Value::Object(attributes) => attributes,
+                _ => serde_json::Map::new(),
+            },
+            trace_id: log.trace_id,
+            span_id: log.span_id,
+            resource: match log.resource {
+                serde_json::Value::Object(resource) => resource,
+                _ => serde_json::Map::new(),
+            },
+            redactions: log.redactions,
         }
     }
 }
@@ -1,2 +1,3 @@
 pub mod disk_log_entry_dto;
 pub mod mapper;
+pub mod rejected_line_dto;
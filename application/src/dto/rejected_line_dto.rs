@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RejectedLineDto {
+    pub path: String,
+    pub byte_offset: i64,
+    pub raw: String,
+    pub error: String,
+    pub parser: serde_json::Value,
+}
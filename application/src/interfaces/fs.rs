@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use notify::Event;
@@ -5,4 +7,16 @@ use notify::Event;
 #[async_trait]
 pub trait FileSystem {
     async fn handle_event(&self, event: Event) -> Result<()>;
+    /// Ingests whatever was written to the files under `root` since their stored offsets,
+    /// e.g. while the server was down.
+    async fn rescan(&self, root: &Path) -> Result<()>;
+    /// Files ending with a multi-line event which wasn't continued for its flush timeout,
+    /// they have to be read again for the event to be stored.
+    fn expired_events(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+    /// Number of stored lines which weren't valid UTF-8.
+    fn invalid_utf8_lines(&self) -> u64 {
+        0
+    }
 }
@@ -1,3 +1,4 @@
 pub mod cache;
 pub mod fs;
+pub mod parser;
 pub mod repository;
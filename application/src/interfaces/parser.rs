@@ -0,0 +1,8 @@
+use anyhow::Result;
+
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Turns a single log line into a log entry.
+pub trait LogParser: Send + Sync {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto>;
+}
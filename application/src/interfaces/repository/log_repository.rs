@@ -1,6 +1,6 @@
-use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+use crate::dto::{disk_log_entry_dto::DiskLogEntryDto, rejected_line_dto::RejectedLineDto};
 use async_trait::async_trait;
-use domain::prelude::{LogEntry, LogEntryFilter, ReposiotryResult};
+use domain::prelude::{FileOffset, LogEntry, LogEntryFilter, ReposiotryResult};
 use uuid::Uuid;
 
 #[async_trait]
@@ -9,5 +9,15 @@ pub trait LogRepository {
     async fn get_logs_by_filter(&self, filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>>;
     async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>>;
     async fn create_log(&self, disk_log_dto: DiskLogEntryDto) -> ReposiotryResult<Uuid>;
+    async fn create_logs(&self, disk_log_dtos: Vec<DiskLogEntryDto>)
+        -> ReposiotryResult<Vec<Uuid>>;
+    /// Creates the log entries and the rejected lines, and stores the offset they were read
+    /// up to atomically, so that each line of a file is ingested exactly once.
+    async fn create_logs_with_offset(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+        rejected_line_dtos: Vec<RejectedLineDto>,
+        offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>>;
     async fn delete_log(&self, id: uuid::Uuid) -> ReposiotryResult<()>;
 }
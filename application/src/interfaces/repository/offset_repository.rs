@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, ReposiotryResult};
+
+#[async_trait]
+pub trait OffsetRepository {
+    async fn get_offset(&self, path: &str) -> ReposiotryResult<Option<FileOffset>>;
+    async fn set_offset(&self, offset: FileOffset) -> ReposiotryResult<()>;
+    async fn delete_offset(&self, path: &str) -> ReposiotryResult<()>;
+}
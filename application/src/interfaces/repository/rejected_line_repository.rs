@@ -0,0 +1,24 @@
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+use async_trait::async_trait;
+use domain::prelude::{RejectedLine, ReposiotryResult};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait RejectedLineRepository {
+    async fn get_rejected_line_by_id(&self, id: Uuid) -> ReposiotryResult<RejectedLine>;
+    async fn get_rejected_lines(
+        &self,
+        path: Option<String>,
+        limit: i64,
+    ) -> ReposiotryResult<Vec<RejectedLine>>;
+    /// Stores the log entry parsed from the rejected line and removes the line atomically.
+    async fn resolve_rejected_line(
+        &self,
+        id: Uuid,
+        disk_log_dto: DiskLogEntryDto,
+    ) -> ReposiotryResult<Uuid>;
+    async fn update_rejected_line_error(&self, id: Uuid, error: &str) -> ReposiotryResult<()>;
+    async fn delete_rejected_line(&self, id: Uuid) -> ReposiotryResult<()>;
+    /// Deletes all the rejected lines, or only those of the file under `path`.
+    async fn purge_rejected_lines(&self, path: Option<String>) -> ReposiotryResult<u64>;
+}
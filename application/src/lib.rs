@@ -1,10 +1,17 @@
 mod dto;
 mod interfaces;
+mod processors;
 
 pub mod prelude {
-    pub use super::dto::disk_log_entry_dto::DiskLogEntryDto;
+    pub use super::dto::{disk_log_entry_dto::DiskLogEntryDto, rejected_line_dto::RejectedLineDto};
     pub use super::interfaces::{
-        cache::Cache, fs::FileSystem, repository::blacklist_repository::BlacklistRepository,
-        repository::log_repository::LogRepository,
+        cache::Cache, fs::FileSystem, parser::LogParser,
+        repository::blacklist_repository::BlacklistRepository,
+        repository::log_repository::LogRepository, repository::offset_repository::OffsetRepository,
+        repository::rejected_line_repository::RejectedLineRepository,
+    };
+    pub use super::processors::{
+        DropMatching, HostAlias, MaskPattern, ProcessedLogRepo, Processor, ProcessorChain,
+        ProcessorConfig, Redact, RedactionMode, RedactionRule, RenameField, SetField,
     };
 }
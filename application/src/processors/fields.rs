@@ -0,0 +1,64 @@
+use serde_json::Value;
+
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Field of an entry, addressed by its name.
+enum Field<'a> {
+    Required(&'a mut String),
+    Optional(&'a mut Option<String>),
+    Attribute(&'a mut serde_json::Map<String, Value>, &'a str),
+}
+
+fn field<'a>(log_entry: &'a mut DiskLogEntryDto, name: &'a str) -> Field<'a> {
+    match name {
+        "timestamp" => Field::Required(&mut log_entry.timestamp),
+        "host" => Field::Required(&mut log_entry.host),
+        "severity" => Field::Required(&mut log_entry.severity),
+        "facility" => Field::Required(&mut log_entry.facility),
+        "syslog_tag" => Field::Required(&mut log_entry.syslog_tag),
+        "source" => Field::Required(&mut log_entry.source),
+        "message" => Field::Required(&mut log_entry.message),
+        "client_identity" => Field::Optional(&mut log_entry.client_identity),
+        "app_name" => Field::Optional(&mut log_entry.app_name),
+        "proc_id" => Field::Optional(&mut log_entry.proc_id),
+        "msg_id" => Field::Optional(&mut log_entry.msg_id),
+        "trace_id" => Field::Optional(&mut log_entry.trace_id),
+        "span_id" => Field::Optional(&mut log_entry.span_id),
+        key => Field::Attribute(&mut log_entry.attributes, key),
+    }
+}
+
+/// Value of the field, attributes which aren't strings as JSON.
+pub(crate) fn get(log_entry: &mut DiskLogEntryDto, name: &str) -> Option<String> {
+    match field(log_entry, name) {
+        Field::Required(value) => Some(value.clone()),
+        Field::Optional(value) => value.clone(),
+        Field::Attribute(attributes, key) => attributes.get(key).map(to_text),
+    }
+}
+
+pub(crate) fn set(log_entry: &mut DiskLogEntryDto, name: &str, value: String) {
+    match field(log_entry, name) {
+        Field::Required(field) => *field = value,
+        Field::Optional(field) => *field = Some(value),
+        Field::Attribute(attributes, key) => {
+            attributes.insert(key.into(), value.into());
+        }
+    }
+}
+
+/// Takes the value out of the field, the required fields are left empty.
+pub(crate) fn take(log_entry: &mut DiskLogEntryDto, name: &str) -> Option<String> {
+    match field(log_entry, name) {
+        Field::Required(value) => Some(std::mem::take(value)),
+        Field::Optional(value) => value.take(),
+        Field::Attribute(attributes, key) => attributes.remove(key).as_ref().map(to_text),
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
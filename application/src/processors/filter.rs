@@ -0,0 +1,27 @@
+use anyhow::Result;
+use regex::Regex;
+
+use super::{fields, Processor};
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Drops the entries with the field matching a regex, e.g. the `debug` severity or
+/// health check requests. Entries without the field are kept.
+pub struct DropMatching {
+    field: String,
+    pattern: Regex,
+}
+
+impl DropMatching {
+    pub fn new(field: &str, pattern: &str) -> Result<Self> {
+        Ok(DropMatching {
+            field: field.into(),
+            pattern: Regex::new(pattern)?,
+        })
+    }
+}
+
+impl Processor for DropMatching {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        !fields::get(log_entry, &self.field).is_some_and(|value| self.pattern.is_match(&value))
+    }
+}
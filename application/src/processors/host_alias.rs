@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+
+use super::Processor;
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Maps the names a host is known under, e.g. its address or short name, onto one name,
+/// so that all its entries are found by that name.
+pub struct HostAlias {
+    aliases: HashMap<String, String>,
+}
+
+impl HostAlias {
+    pub fn new(aliases: HashMap<String, String>) -> Self {
+        HostAlias { aliases }
+    }
+}
+
+impl Processor for HostAlias {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        if let Some(host) = self.aliases.get(&log_entry.host) {
+            log_entry.host = host.clone();
+        }
+
+        true
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use regex::Regex;
+
+use super::{fields, Processor};
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Replaces the matches of a regex within a field, e.g. passwords or card numbers in the
+/// message. The replacement may refer to the groups of the regex, e.g. `$1`.
+pub struct MaskPattern {
+    field: String,
+    pattern: Regex,
+    replacement: String,
+}
+
+impl MaskPattern {
+    pub fn new(field: &str, pattern: &str, replacement: &str) -> Result<Self> {
+        Ok(MaskPattern {
+            field: field.into(),
+            pattern: Regex::new(pattern)?,
+            replacement: replacement.into(),
+        })
+    }
+}
+
+impl Processor for MaskPattern {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        if let Some(value) = fields::get(log_entry, &self.field) {
+            let masked = self.pattern.replace_all(&value, self.replacement.as_str());
+
+            if masked != value {
+                fields::set(log_entry, &self.field, masked.into_owned());
+            }
+        }
+
+        true
+    }
+}
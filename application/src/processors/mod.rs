@@ -0,0 +1,157 @@
+mod fields;
+mod filter;
+mod host_alias;
+mod mask;
+mod redact;
+mod rename;
+mod repository;
+mod set;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+pub use filter::DropMatching;
+pub use host_alias::HostAlias;
+pub use mask::MaskPattern;
+pub use redact::{Redact, RedactionMode, RedactionRule};
+pub use rename::RenameField;
+pub use repository::ProcessedLogRepo;
+pub use set::SetField;
+
+/// Step of the processor chain, changing or dropping an entry before it's stored.
+///
+/// The fields are named after the fields of `DiskLogEntryDto`, e.g. `host` or `message`,
+/// any other name is a key of the attributes.
+pub trait Processor: Send + Sync {
+    /// Changes the entry in place, returns `false` if the entry should be dropped.
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool;
+
+    /// Rewrites the personal data within text stored apart from the entries, e.g. a rejected
+    /// line. Only the processors redacting personal data change it.
+    fn redact_text(&self, _text: &mut String) {}
+}
+
+/// Configuration of a processor, selected by its `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProcessorConfig {
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// Copies the field, keeping the original.
+    Copy {
+        from: String,
+        to: String,
+    },
+    /// Drops the entries with the field matching the regex.
+    Drop {
+        field: String,
+        pattern: String,
+    },
+    /// Replaces the matches of the regex within the field.
+    Mask {
+        #[serde(default = "default_mask_field")]
+        field: String,
+        pattern: String,
+        #[serde(default = "default_mask_replacement")]
+        replacement: String,
+    },
+    Set {
+        field: String,
+        value: String,
+    },
+    /// Replaces the hosts known under other names, e.g. their addresses.
+    HostAlias {
+        aliases: HashMap<String, String>,
+    },
+    /// Rewrites the personal data matched by the rules within the fields.
+    Redact {
+        #[serde(default = "default_redact_fields")]
+        fields: Vec<String>,
+        rules: Vec<RedactionRule>,
+        /// Key of the hashes of the `hash` mode, required by the rules using it.
+        hash_key: Option<String>,
+    },
+}
+
+fn default_mask_field() -> String {
+    "message".into()
+}
+
+fn default_mask_replacement() -> String {
+    "***".into()
+}
+
+fn default_redact_fields() -> Vec<String> {
+    vec!["message".into()]
+}
+
+impl ProcessorConfig {
+    pub fn build(&self) -> Result<Box<dyn Processor>> {
+        Ok(match self {
+            ProcessorConfig::Rename { from, to } => Box::new(RenameField::new(from, to)),
+            ProcessorConfig::Copy { from, to } => Box::new(RenameField::copy(from, to)),
+            ProcessorConfig::Drop { field, pattern } => {
+                Box::new(DropMatching::new(field, pattern)?)
+            }
+            ProcessorConfig::Mask {
+                field,
+                pattern,
+                replacement,
+            } => Box::new(MaskPattern::new(field, pattern, replacement)?),
+            ProcessorConfig::Set { field, value } => Box::new(SetField::new(field, value)),
+            ProcessorConfig::HostAlias { aliases } => Box::new(HostAlias::new(aliases.clone())),
+            ProcessorConfig::Redact {
+                fields,
+                rules,
+                hash_key,
+            } => Box::new(Redact::new(fields, rules, hash_key.as_deref())?),
+        })
+    }
+}
+
+/// Processors run in order on every entry before it's stored, whichever input it came from.
+#[derive(Default)]
+pub struct ProcessorChain {
+    processors: Vec<Box<dyn Processor>>,
+}
+
+impl ProcessorChain {
+    pub fn new(configs: &[ProcessorConfig]) -> Result<Self> {
+        Ok(ProcessorChain {
+            processors: configs
+                .iter()
+                .map(ProcessorConfig::build)
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn with(mut self, processor: impl Processor + 'static) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Runs the processors on the entry, `None` if one of them dropped it.
+    pub fn process(&self, mut log_entry: DiskLogEntryDto) -> Option<DiskLogEntryDto> {
+        self.processors
+            .iter()
+            .all(|processor| processor.process(&mut log_entry))
+            .then_some(log_entry)
+    }
+
+    /// Runs the redaction of the processors on text stored apart from the entries.
+    pub fn redact_text(&self, text: &mut String) {
+        for processor in &self.processors {
+            processor.redact_text(text);
+        }
+    }
+}
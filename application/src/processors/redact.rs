@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+use hmac::{Hmac, Mac};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use super::{fields, Processor};
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Number of bytes of the hash kept in the replacement.
+const HASH_LEN: usize = 8;
+
+/// Patterns of the built-in rules, selected by leaving out the pattern of a rule.
+const BUILT_IN: [(&str, &str); 3] = [
+    (
+        "email",
+        r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+    ),
+    // Validated with the Luhn checksum, so that e.g. timestamps and ids aren't redacted
+    ("credit_card", r"\b\d(?:[ -]?\d){12,18}\b"),
+    ("bearer_token", r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]+=*"),
+];
+
+/// Rule of the `redact` processor. Without a pattern, the rule is the built-in rule with
+/// its name: `email`, `credit_card` or `bearer_token`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    pub name: String,
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub mode: RedactionMode,
+    /// Replacement of the `replace` mode, `[REDACTED:<name>]` by default. It may refer
+    /// to the groups of the pattern, e.g. `$1`.
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    #[default]
+    Replace,
+    /// Replaces the match with a keyed hash of it, `[<name>:<hash>]`, so that the entries
+    /// with the same value can still be correlated.
+    Hash,
+}
+
+struct Rule {
+    name: String,
+    pattern: Regex,
+    mode: RedactionMode,
+    replacement: String,
+    /// Only the matches passing the Luhn checksum are redacted.
+    luhn: bool,
+}
+
+/// Rewrites the personal data matched by the rules, e.g. emails or card numbers, and
+/// records the names of the rules which matched in the `redactions` of the entry.
+pub struct Redact {
+    fields: Vec<String>,
+    rules: Vec<Rule>,
+    hash_key: Option<Vec<u8>>,
+}
+
+impl Redact {
+    pub fn new(fields: &[String], rules: &[RedactionRule], hash_key: Option<&str>) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let pattern = match &rule.pattern {
+                    Some(pattern) => pattern.as_str(),
+                    None => BUILT_IN
+                        .iter()
+                        .find(|(name, _)| *name == rule.name)
+                        .map(|(_, pattern)| *pattern)
+                        .ok_or_else(|| anyhow!("Redaction rule '{}' has no pattern", rule.name))?,
+                };
+
+                let luhn = rule.pattern.is_none() && rule.name == "credit_card";
+
+                if rule.mode == RedactionMode::Hash && hash_key.is_none() {
+                    bail!("Redaction rule '{}' hashes without a hash key", rule.name);
+                }
+
+                Ok(Rule {
+                    name: rule.name.clone(),
+                    pattern: Regex::new(pattern)?,
+                    mode: rule.mode,
+                    replacement: rule
+                        .replacement
+                        .clone()
+                        .unwrap_or_else(|| format!("[REDACTED:{}]", rule.name)),
+                    luhn,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Redact {
+            fields: fields.to_vec(),
+            rules,
+            hash_key: hash_key.map(|key| key.as_bytes().to_vec()),
+        })
+    }
+
+    /// Rewrites the matches of the rules within the value, `redacted` is called with every
+    /// rule which matched.
+    fn redact_value(&self, value: &mut String, mut redacted: impl FnMut(&Rule)) {
+        for rule in &self.rules {
+            let mut matched = false;
+            let rewritten =
+                rule.pattern
+                    .replace_all(value, |caps: &Captures| match self.redact(rule, caps) {
+                        Some(replacement) => {
+                            matched = true;
+                            replacement
+                        }
+                        None => caps[0].to_owned(),
+                    });
+
+            if matched {
+                *value = rewritten.into_owned();
+                redacted(rule);
+            }
+        }
+    }
+
+    fn redact(&self, rule: &Rule, caps: &Captures) -> Option<String> {
+        let matched = &caps[0];
+
+        if rule.luhn && !luhn(matched) {
+            return None;
+        }
+
+        Some(match rule.mode {
+            RedactionMode::Replace => {
+                let mut replacement = String::new();
+                caps.expand(&rule.replacement, &mut replacement);
+                replacement
+            }
+            RedactionMode::Hash => {
+                let key = self.hash_key.as_deref().expect("Hash key is checked");
+                let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes any key");
+                mac.update(matched.as_bytes());
+
+                let mut hash = String::new();
+                for b in &mac.finalize().into_bytes()[..HASH_LEN] {
+                    let _ = write!(hash, "{b:02x}");
+                }
+
+                format!("[{}:{hash}]", rule.name)
+            }
+        })
+    }
+}
+
+impl Processor for Redact {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        for field in &self.fields {
+            let mut value = match fields::get(log_entry, field) {
+                Some(value) => value,
+                None => continue,
+            };
+            let mut changed = false;
+
+            self.redact_value(&mut value, |rule| {
+                changed = true;
+
+                if !log_entry.redactions.contains(&rule.name) {
+                    log_entry.redactions.push(rule.name.clone());
+                }
+            });
+
+            if changed {
+                fields::set(log_entry, field, value);
+            }
+        }
+
+        true
+    }
+
+    fn redact_text(&self, text: &mut String) {
+        self.redact_value(text, |_| {});
+    }
+}
+
+/// Luhn checksum of the digits of a card number.
+fn luhn(number: &str) -> bool {
+    let sum: u32 = number
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| match i % 2 {
+            0 => digit,
+            _ if digit > 4 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
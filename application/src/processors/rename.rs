@@ -0,0 +1,43 @@
+use super::{fields, Processor};
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Moves or copies the value of a field into another one, e.g. an attribute into `source`.
+/// Entries without the field are left as they are.
+pub struct RenameField {
+    from: String,
+    to: String,
+    keep: bool,
+}
+
+impl RenameField {
+    pub fn new(from: &str, to: &str) -> Self {
+        RenameField {
+            from: from.into(),
+            to: to.into(),
+            keep: false,
+        }
+    }
+
+    /// Copies the field instead, keeping the original.
+    pub fn copy(from: &str, to: &str) -> Self {
+        RenameField {
+            keep: true,
+            ..RenameField::new(from, to)
+        }
+    }
+}
+
+impl Processor for RenameField {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        let value = match self.keep {
+            true => fields::get(log_entry, &self.from),
+            false => fields::take(log_entry, &self.from),
+        };
+
+        if let Some(value) = value {
+            fields::set(log_entry, &self.to, value);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, LogEntry, LogEntryFilter, ReposiotryResult};
+use uuid::Uuid;
+
+use super::ProcessorChain;
+use crate::{
+    dto::{disk_log_entry_dto::DiskLogEntryDto, rejected_line_dto::RejectedLineDto},
+    interfaces::repository::{log_repository::LogRepository, offset_repository::OffsetRepository},
+};
+
+/// Log repository running the processor chain on the entries before storing them, so that
+/// every input passing its entries to the repository goes through the same chain.
+///
+/// The dropped entries get the nil id, so that the ids still line up with the entries.
+#[derive(Clone)]
+pub struct ProcessedLogRepo<L> {
+    inner: L,
+    chain: Arc<ProcessorChain>,
+}
+
+impl<L> ProcessedLogRepo<L> {
+    pub fn new(inner: L, chain: Arc<ProcessorChain>) -> Self {
+        ProcessedLogRepo { inner, chain }
+    }
+
+    /// Runs the chain on the entries, keeping the positions of the stored ones.
+    fn process(&self, dtos: Vec<DiskLogEntryDto>) -> (Vec<DiskLogEntryDto>, Vec<bool>) {
+        let mut kept = Vec::with_capacity(dtos.len());
+        let processed = dtos
+            .into_iter()
+            .filter_map(|dto| {
+                let processed = self.chain.process(dto);
+                kept.push(processed.is_some());
+                processed
+            })
+            .collect();
+
+        (processed, kept)
+    }
+}
+
+/// Puts the nil id in place of the dropped entries.
+fn line_up(ids: Vec<Uuid>, kept: Vec<bool>) -> Vec<Uuid> {
+    let mut ids = ids.into_iter();
+
+    kept.into_iter()
+        .map(|kept| match kept {
+            true => ids.next().unwrap_or_default(),
+            false => Uuid::nil(),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl<L> LogRepository for ProcessedLogRepo<L>
+where
+    L: LogRepository + Send + Sync,
+{
+    async fn get_log_by_id(&self, id: Uuid) -> ReposiotryResult<LogEntry> {
+        self.inner.get_log_by_id(id).await
+    }
+
+    async fn get_logs_by_filter(&self, filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
+        self.inner.get_logs_by_filter(filter).await
+    }
+
+    async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>> {
+        self.inner.get_all_logs().await
+    }
+
+    async fn create_log(&self, disk_log_dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
+        match self.chain.process(disk_log_dto) {
+            Some(dto) => self.inner.create_log(dto).await,
+            None => Ok(Uuid::nil()),
+        }
+    }
+
+    async fn create_logs(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        let (dtos, kept) = self.process(disk_log_dtos);
+        let ids = self.inner.create_logs(dtos).await?;
+
+        Ok(line_up(ids, kept))
+    }
+
+    async fn create_logs_with_offset(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+        rejected_line_dtos: Vec<RejectedLineDto>,
+        offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        let (dtos, kept) = self.process(disk_log_dtos);
+        // Rejected lines aren't entries, yet they hold the same personal data
+        let rejected_line_dtos = rejected_line_dtos
+            .into_iter()
+            .map(|mut dto| {
+                self.chain.redact_text(&mut dto.raw);
+                self.chain.redact_text(&mut dto.error);
+                dto
+            })
+            .collect();
+        let ids = self
+            .inner
+            .create_logs_with_offset(dtos, rejected_line_dtos, offset)
+            .await?;
+
+        Ok(line_up(ids, kept))
+    }
+
+    async fn delete_log(&self, id: Uuid) -> ReposiotryResult<()> {
+        self.inner.delete_log(id).await
+    }
+}
+
+#[async_trait]
+impl<L> OffsetRepository for ProcessedLogRepo<L>
+where
+    L: OffsetRepository + Send + Sync,
+{
+    async fn get_offset(&self, path: &str) -> ReposiotryResult<Option<FileOffset>> {
+        self.inner.get_offset(path).await
+    }
+
+    async fn set_offset(&self, offset: FileOffset) -> ReposiotryResult<()> {
+        self.inner.set_offset(offset).await
+    }
+
+    async fn delete_offset(&self, path: &str) -> ReposiotryResult<()> {
+        self.inner.delete_offset(path).await
+    }
+}
@@ -0,0 +1,24 @@
+use super::{fields, Processor};
+use crate::dto::disk_log_entry_dto::DiskLogEntryDto;
+
+/// Sets a field to a static value, e.g. the environment or the datacenter as an attribute.
+pub struct SetField {
+    field: String,
+    value: String,
+}
+
+impl SetField {
+    pub fn new(field: &str, value: &str) -> Self {
+        SetField {
+            field: field.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl Processor for SetField {
+    fn process(&self, log_entry: &mut DiskLogEntryDto) -> bool {
+        fields::set(log_entry, &self.field, self.value.clone());
+        true
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use application::prelude::{DiskLogEntryDto, LogRepository, RejectedLineDto};
+use async_trait::async_trait;
+use chrono::Utc;
+use domain::prelude::{
+    FileOffset, LogEntry, LogEntryFilter, ReposiotryResult, RepositoryError, StructuredData,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Log repository keeping everything in memory, in the order it was stored.
+#[derive(Default, Clone)]
+pub struct MemoryLogRepo {
+    pub logs: Arc<Mutex<Vec<LogEntry>>>,
+    pub rejected_lines: Arc<Mutex<Vec<RejectedLineDto>>>,
+    pub offsets: Arc<Mutex<Vec<FileOffset>>>,
+}
+
+#[async_trait]
+impl LogRepository for MemoryLogRepo {
+    async fn get_log_by_id(&self, id: Uuid) -> ReposiotryResult<LogEntry> {
+        self.logs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|log| log.id == id)
+            .cloned()
+            .ok_or(RepositoryError::Database(sqlx::Error::RowNotFound))
+    }
+
+    async fn get_logs_by_filter(&self, filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
+        let attributes = match &filter.attributes {
+            Some(attributes) => Some(StructuredData::parse(attributes.trim())?.0.into_value()),
+            None => None,
+        };
+        let equals = |expected: &Option<String>, value: &str| {
+            expected.as_deref().is_none_or(|expected| expected == value)
+        };
+
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log| {
+                filter.time.is_none_or(|time| time == log.timestamp)
+                    && equals(&filter.host, &log.host)
+                    && equals(&filter.severity, &log.severity)
+                    && equals(&filter.facility, &log.facility)
+                    && equals(&filter.syslog_tag, &log.syslog_tag)
+                    && equals(&filter.source, &log.source)
+                    && equals(
+                        &filter.trace_id,
+                        log.trace_id.as_deref().unwrap_or_default(),
+                    )
+                    && attributes
+                        .as_ref()
+                        .is_none_or(|attributes| contains(&log.attributes, attributes))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>> {
+        Ok(self.logs.lock().unwrap().clone())
+    }
+
+    async fn create_log(&self, disk_log_dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
+        let log = to_log_entry(disk_log_dto);
+        let id = log.id;
+        self.logs.lock().unwrap().push(log);
+        Ok(id)
+    }
+
+    async fn create_logs(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        let logs: Vec<_> = disk_log_dtos.into_iter().map(to_log_entry).collect();
+        let ids = logs.iter().map(|log| log.id).collect();
+        self.logs.lock().unwrap().extend(logs);
+        Ok(ids)
+    }
+
+    async fn create_logs_with_offset(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+        rejected_line_dtos: Vec<RejectedLineDto>,
+        offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        self.rejected_lines
+            .lock()
+            .unwrap()
+            .extend(rejected_line_dtos);
+        self.offsets.lock().unwrap().push(offset);
+        self.create_logs(disk_log_dtos).await
+    }
+
+    async fn delete_log(&self, id: Uuid) -> ReposiotryResult<()> {
+        let mut logs = self.logs.lock().unwrap();
+        let len = logs.len();
+        logs.retain(|log| log.id != id);
+
+        match logs.len() < len {
+            true => Ok(()),
+            false => Err(RepositoryError::Database(sqlx::Error::RowNotFound)),
+        }
+    }
+}
+
+fn to_log_entry(dto: DiskLogEntryDto) -> LogEntry {
+    LogEntry {
+        id: Uuid::new_v4(),
+        timestamp: dto.timestamp.parse().unwrap_or_default(),
+        host: dto.host,
+        severity: dto.severity,
+        facility: dto.facility,
+        syslog_tag: dto.syslog_tag,
+        source: dto.source,
+        message: dto.message,
+        client_identity: dto.client_identity,
+        app_name: dto.app_name,
+        proc_id: dto.proc_id,
+        msg_id: dto.msg_id,
+        attributes: Value::Object(dto.attributes),
+        trace_id: dto.trace_id,
+        span_id: dto.span_id,
+        resource: Value::Object(dto.resource),
+        redactions: dto.redactions,
+        ingested_at: Utc::now(),
+    }
+}
+
+/// Tells whether `value` contains `other`, as the `@>` operator of Postgres does.
+fn contains(value: &Value, other: &Value) -> bool {
+    match (value, other) {
+        (Value::Object(value), Value::Object(other)) => other
+            .iter()
+            .all(|(key, other)| value.get(key).is_some_and(|value| contains(value, other))),
+        (Value::Array(value), Value::Array(other)) => other
+            .iter()
+            .all(|other| value.iter().any(|value| contains(value, other))),
+        _ => value == other,
+    }
+}
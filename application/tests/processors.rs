@@ -0,0 +1,110 @@
+mod common;
+
+use std::sync::Arc;
+
+use application::prelude::{
+    DiskLogEntryDto, DropMatching, LogRepository, ProcessedLogRepo, ProcessorChain, ProcessorConfig,
+};
+use common::MemoryLogRepo;
+use serde_json::json;
+
+#[test]
+fn processors_run_in_order() {
+    let configs: Vec<ProcessorConfig> = serde_json::from_value(json!([
+        { "type": "host_alias", "aliases": { "10.0.0.12": "web-1" } },
+        { "type": "rename", "from": "container_name", "to": "app_name" },
+        { "type": "copy", "from": "host", "to": "origin" },
+        { "type": "mask", "pattern": r"password=\S+", "replacement": "password=***" },
+        { "type": "set", "field": "datacenter", "value": "eu-west" },
+        { "type": "set", "field": "source", "value": "web" },
+    ]))
+    .unwrap();
+    let chain = ProcessorChain::new(&configs).unwrap();
+
+    let mut attributes = serde_json::Map::new();
+    attributes.insert("container_name".into(), json!("nginx"));
+    let log_entry = DiskLogEntryDto {
+        host: "10.0.0.12".into(),
+        message: "login user=bob password=hunter2 ok".into(),
+        attributes,
+        ..Default::default()
+    };
+
+    let log_entry = chain.process(log_entry).unwrap();
+
+    assert_eq!(log_entry.host, "web-1");
+    assert_eq!(log_entry.app_name.as_deref(), Some("nginx"));
+    assert_eq!(log_entry.source, "web");
+    assert_eq!(log_entry.message, "login user=bob password=*** ok");
+    assert_eq!(
+        serde_json::Value::Object(log_entry.attributes),
+        json!({ "origin": "web-1", "datacenter": "eu-west" })
+    );
+}
+
+#[test]
+fn drop_matching_entries() {
+    let chain = ProcessorChain::default()
+        .with(DropMatching::new("severity", "^debug$").unwrap())
+        .with(DropMatching::new("path", "^/health_check").unwrap());
+
+    let debug = DiskLogEntryDto {
+        severity: "debug".into(),
+        ..Default::default()
+    };
+    let mut health_check = DiskLogEntryDto {
+        severity: "info".into(),
+        ..Default::default()
+    };
+    health_check
+        .attributes
+        .insert("path".into(), json!("/health_check"));
+    let info = DiskLogEntryDto {
+        severity: "info".into(),
+        ..Default::default()
+    };
+
+    assert!(chain.process(debug).is_none());
+    assert!(chain.process(health_check).is_none());
+    assert!(chain.process(info).is_some());
+}
+
+#[test]
+fn invalid_pattern_is_rejected() {
+    let configs: Vec<ProcessorConfig> = serde_json::from_value(json!([
+        { "type": "drop", "field": "message", "pattern": "(" },
+    ]))
+    .unwrap();
+
+    assert!(ProcessorChain::new(&configs).is_err());
+}
+
+#[tokio::test]
+async fn dropped_entries_get_the_nil_id() {
+    let log_repo = MemoryLogRepo::default();
+    let chain = ProcessorChain::default().with(DropMatching::new("message", "noise").unwrap());
+    let processed_repo = ProcessedLogRepo::new(log_repo.clone(), Arc::new(chain));
+
+    let log_entries = ["first", "noise", "second"]
+        .into_iter()
+        .map(|message| DiskLogEntryDto {
+            message: message.into(),
+            ..Default::default()
+        })
+        .collect();
+
+    let ids = processed_repo.create_logs(log_entries).await.unwrap();
+
+    assert_eq!(ids.len(), 3);
+    assert!(!ids[0].is_nil());
+    assert!(ids[1].is_nil());
+    assert!(!ids[2].is_nil());
+
+    let logs = log_repo.get_all_logs().await.unwrap();
+    let messages: Vec<_> = logs.iter().map(|log| log.message.as_str()).collect();
+    assert_eq!(messages, ["first", "second"]);
+    assert_eq!(
+        log_repo.get_log_by_id(ids[2]).await.unwrap().message,
+        "second"
+    );
+}
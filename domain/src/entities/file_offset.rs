@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Position up to which a watched file was ingested.
+///
+/// The device and inode numbers identify the file under `path`. For compressed files
+/// `byte_offset` counts decompressed bytes and `complete` marks an archive read to the end.
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow, PartialEq, Eq)]
+pub struct FileOffset {
+    pub path: String,
+    pub dev: i64,
+    pub ino: i64,
+    pub byte_offset: i64,
+    pub complete: bool,
+}
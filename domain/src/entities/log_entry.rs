@@ -10,4 +10,16 @@ pub struct LogEntry {
     pub syslog_tag: String,
     pub source: String,
     pub message: String,
+    pub client_identity: Option<String>,
+    pub app_name: Option<String>,
+    pub proc_id: Option<String>,
+    pub msg_id: Option<String>,
+    pub attributes: serde_json::Value,
+    pub trace_id: Option<String>,
+    pub span_id: Option<String>,
+    pub resource: serde_json::Value,
+    /// Names of the redaction rules which rewrote the entry before it was stored.
+    pub redactions: Vec<String>,
+    /// Time the entry was stored, as opposed to the time of the event in `timestamp`.
+    pub ingested_at: chrono::DateTime<chrono::Utc>,
 }
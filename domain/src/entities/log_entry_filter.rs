@@ -1,7 +1,8 @@
 use serde::Deserialize;
 use sqlx::{postgres::PgArguments, query::QueryAs, Arguments, Postgres, QueryBuilder};
 
-use super::log_entry::LogEntry;
+use super::{log_entry::LogEntry, structured_data::StructuredData};
+use crate::prelude::{ReposiotryResult, RepositoryError};
 
 #[derive(Debug, Deserialize)]
 pub struct LogEntryFilter {
@@ -11,6 +12,9 @@ pub struct LogEntryFilter {
     pub facility: Option<String>,
     pub syslog_tag: Option<String>,
     pub source: Option<String>,
+    /// Structured data the log's attributes have to contain, e.g. `[exampleSDID@32473 iut="3"]`.
+    pub attributes: Option<String>,
+    pub trace_id: Option<String>,
 }
 
 struct Filter<'a> {
@@ -20,36 +24,69 @@ struct Filter<'a> {
     facility: Option<FilterField<'a>>,
     syslog_tag: Option<FilterField<'a>>,
     source: Option<FilterField<'a>>,
+    attributes: Option<FilterField<'a>>,
+    trace_id: Option<FilterField<'a>>,
 }
 
 impl<'a> Filter<'a> {
-    fn new(filter: LogEntryFilter) -> Self {
-        Self {
+    fn new(filter: LogEntryFilter) -> ReposiotryResult<Self> {
+        let attributes = match filter.attributes {
+            Some(attributes) => {
+                let (structured_data, rest) = StructuredData::parse(attributes.trim())?;
+
+                if !rest.is_empty() {
+                    return Err(RepositoryError::Parsing(format!(
+                        "Unexpected input after the attributes filter: {rest:?}"
+                    )));
+                }
+
+                Some(FilterField {
+                    name: "attributes",
+                    value: structured_data.into_value().to_string(),
+                    kind: FilterKind::Contains,
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
             time: filter.time.map(|t| FilterField {
                 name: "time",
                 value: t.to_rfc3339(),
+                kind: FilterKind::Equals,
             }),
             host: filter.host.map(|h| FilterField {
                 name: "host",
                 value: h,
+                kind: FilterKind::Equals,
             }),
             severity: filter.severity.map(|s| FilterField {
                 name: "severity",
                 value: s,
+                kind: FilterKind::Equals,
             }),
             facility: filter.facility.map(|f| FilterField {
                 name: "facility",
                 value: f,
+                kind: FilterKind::Equals,
             }),
             syslog_tag: filter.syslog_tag.map(|s| FilterField {
                 name: "syslog_tag",
                 value: s,
+                kind: FilterKind::Equals,
             }),
             source: filter.source.map(|s| FilterField {
                 name: "source",
                 value: s,
+                kind: FilterKind::Equals,
             }),
-        }
+            attributes,
+            trace_id: filter.trace_id.map(|t| FilterField {
+                name: "trace_id",
+                value: t,
+                kind: FilterKind::Equals,
+            }),
+        })
     }
 
     fn into_vec(self) -> Vec<Option<FilterField<'a>>> {
@@ -60,6 +97,8 @@ impl<'a> Filter<'a> {
             self.facility,
             self.syslog_tag,
             self.source,
+            self.attributes,
+            self.trace_id,
         ]
     }
 }
@@ -68,6 +107,13 @@ impl<'a> Filter<'a> {
 struct FilterField<'a> {
     name: &'a str,
     value: String,
+    kind: FilterKind,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+enum FilterKind {
+    Equals,
+    Contains,
 }
 
 pub struct LogEntryFilterQueryBuilder<'a> {
@@ -76,22 +122,28 @@ pub struct LogEntryFilterQueryBuilder<'a> {
 }
 
 impl<'a> LogEntryFilterQueryBuilder<'a> {
-    pub fn new(filter: LogEntryFilter) -> Self {
-        Self {
+    pub fn new(filter: LogEntryFilter) -> ReposiotryResult<Self> {
+        Ok(Self {
             builder: QueryBuilder::new("SELECT * FROM logs WHERE "),
-            filter: Filter::new(filter).into_vec(),
-        }
+            filter: Filter::new(filter)?.into_vec(),
+        })
     }
 
-    pub fn to_sql_query(&'a mut self) -> QueryAs<Postgres, LogEntry, PgArguments> {
+    pub fn to_sql_query(&'a mut self) -> QueryAs<'a, Postgres, LogEntry, PgArguments> {
         let mut to_bind = PgArguments::default();
 
         for field in self.filter.iter().flatten() {
-            self.builder
-                .push(&field.name)
-                .push(" = ")
-                .push_bind(&field.value)
-                .push(" AND ");
+            self.builder.push(field.name);
+
+            match field.kind {
+                FilterKind::Equals => self.builder.push(" = ").push_bind(&field.value),
+                FilterKind::Contains => self
+                    .builder
+                    .push(" @> ")
+                    .push_bind(&field.value)
+                    .push("::jsonb"),
+            }
+            .push(" AND ");
             to_bind.add(&field.value);
         }
 
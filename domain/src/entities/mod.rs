@@ -1,3 +1,6 @@
 pub mod blacklist_entry;
+pub mod file_offset;
 pub mod log_entry;
 pub mod log_entry_filter;
+pub mod rejected_line;
+pub mod structured_data;
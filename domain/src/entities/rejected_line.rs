@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Line of a watched file which couldn't be parsed, kept aside to be inspected and retried.
+///
+/// `parser` holds the configuration of the parser which rejected the line.
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow, PartialEq, Eq)]
+pub struct RejectedLine {
+    pub id: uuid::Uuid,
+    pub path: String,
+    pub byte_offset: i64,
+    pub raw: String,
+    pub error: String,
+    pub parser: serde_json::Value,
+    pub rejected_at: chrono::DateTime<chrono::Utc>,
+}
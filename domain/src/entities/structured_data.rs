@@ -0,0 +1,110 @@
+use serde_json::{Map, Value};
+
+use crate::prelude::{ReposiotryResult, RepositoryError};
+
+/// STRUCTURED-DATA part of a RFC 5424 message, e.g. `[exampleSDID@32473 iut="3" eventSource="App"]`.
+///
+/// Elements are kept as a JSON object keyed by SD-ID, holding objects of their parameters.
+/// A parameter repeated within one element is stored as an array of its values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StructuredData(pub Map<String, Value>);
+
+impl StructuredData {
+    /// Parses structured data from the beginning of `input` and returns the remaining input.
+    ///
+    /// The NILVALUE (`-`) is parsed as empty structured data.
+    pub fn parse(input: &str) -> ReposiotryResult<(Self, &str)> {
+        if let Some(rest) = input.strip_prefix('-') {
+            return Ok((Self::default(), rest));
+        }
+
+        let mut elements = Map::new();
+        let mut rest = input;
+
+        while let Some(element) = rest.strip_prefix('[') {
+            let (id, mut params_input) = split_name(element)?;
+            let mut params = Map::new();
+
+            loop {
+                if let Some(after) = params_input.strip_prefix(']') {
+                    rest = after;
+                    break;
+                }
+
+                let after_space = params_input
+                    .strip_prefix(' ')
+                    .ok_or_else(|| parsing_error("Expected space before SD-PARAM", params_input))?;
+                let (name, after_name) = split_name(after_space)?;
+                let after_eq = after_name
+                    .strip_prefix("=\"")
+                    .ok_or_else(|| parsing_error("Expected '=\"' after PARAM-NAME", after_name))?;
+                let (value, after_value) = split_param_value(after_eq)?;
+
+                match params.get_mut(name) {
+                    Some(Value::Array(values)) => values.push(Value::String(value)),
+                    Some(existing) => {
+                        let first = existing.take();
+                        *existing = Value::Array(vec![first, Value::String(value)]);
+                    }
+                    None => {
+                        params.insert(name.into(), Value::String(value));
+                    }
+                }
+
+                params_input = after_value;
+            }
+
+            elements.insert(id.into(), Value::Object(params));
+        }
+
+        if elements.is_empty() {
+            return Err(parsing_error("Expected '-' or '['", input));
+        }
+
+        Ok((Self(elements), rest))
+    }
+
+    pub fn into_value(self) -> Value {
+        Value::Object(self.0)
+    }
+}
+
+/// Splits an SD-NAME (SD-ID or PARAM-NAME) terminated by `=`, space, `]` or `"`.
+fn split_name(input: &str) -> ReposiotryResult<(&str, &str)> {
+    let end = input
+        .find(['=', ' ', ']', '"'])
+        .ok_or_else(|| parsing_error("Unterminated structured data", input))?;
+
+    if end == 0 || end > 32 {
+        return Err(parsing_error("Invalid SD-NAME", input));
+    }
+
+    Ok((&input[..end], &input[end..]))
+}
+
+/// Splits a PARAM-VALUE terminated by an unescaped `"`, unescaping `\"`, `\\` and `\]`.
+fn split_param_value(input: &str) -> ReposiotryResult<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &input[idx + 1..])),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\' | ']'))) => value.push(escaped),
+                Some((_, other)) => {
+                    value.push('\\');
+                    value.push(other);
+                }
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+
+    Err(parsing_error("Unterminated PARAM-VALUE", input))
+}
+
+fn parsing_error(reason: &str, input: &str) -> RepositoryError {
+    RepositoryError::Parsing(format!("{reason} in structured data: {input:?}"))
+}
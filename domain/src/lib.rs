@@ -3,8 +3,9 @@ mod errors;
 
 pub mod prelude {
     pub use super::entities::{
-        blacklist_entry::BlacklistEntry, log_entry::LogEntry, log_entry_filter::LogEntryFilter,
-        log_entry_filter::LogEntryFilterQueryBuilder,
+        blacklist_entry::BlacklistEntry, file_offset::FileOffset, log_entry::LogEntry,
+        log_entry_filter::LogEntryFilter, log_entry_filter::LogEntryFilterQueryBuilder,
+        rejected_line::RejectedLine, structured_data::StructuredData,
     };
     pub use super::errors::{ReposiotryResult, RepositoryError};
 }
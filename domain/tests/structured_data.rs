@@ -0,0 +1,105 @@
+use domain::prelude::{LogEntryFilter, LogEntryFilterQueryBuilder, StructuredData};
+use serde_json::json;
+use sqlx::Execute;
+
+#[test]
+fn parse_structured_data_elements() {
+    let (structured_data, rest) = StructuredData::parse(
+        r#"[exampleSDID@32473 iut="3" eventSource="App"][examplePriority@32473 class="high"] message"#,
+    )
+    .expect("Cannot parse structured data");
+
+    assert_eq!(rest, " message");
+    assert_eq!(
+        structured_data.into_value(),
+        json!({
+            "exampleSDID@32473": { "iut": "3", "eventSource": "App" },
+            "examplePriority@32473": { "class": "high" },
+        })
+    );
+}
+
+#[test]
+fn parse_escaped_and_repeated_param_values() {
+    let (structured_data, rest) = StructuredData::parse(
+        r#"[meta quote="say \"hi\"" bracket="a\]b" backslash="c:\\d" other="\n" ip="1" ip="2"]"#,
+    )
+    .expect("Cannot parse structured data");
+
+    assert_eq!(rest, "");
+    assert_eq!(
+        structured_data.into_value(),
+        json!({
+            "meta": {
+                "quote": r#"say "hi""#,
+                "bracket": "a]b",
+                "backslash": r"c:\d",
+                "other": r"\n",
+                "ip": ["1", "2"],
+            },
+        })
+    );
+}
+
+#[test]
+fn parse_nil_structured_data() {
+    let (structured_data, rest) = StructuredData::parse("- message").unwrap();
+
+    assert_eq!(structured_data, StructuredData::default());
+    assert_eq!(rest, " message");
+}
+
+#[test]
+fn reject_invalid_structured_data() {
+    for input in [
+        "",
+        "message",
+        "[]",
+        "[meta",
+        r#"[meta ip="1"#,
+        r#"[meta ip=1]"#,
+        r#"[meta  ip="1"]"#,
+        r#"[meta ip="1"x]"#,
+        "[an_sd_id_longer_than_thirty_two_chars]",
+    ] {
+        assert!(
+            StructuredData::parse(input).is_err(),
+            "{input:?} was parsed"
+        );
+    }
+}
+
+#[test]
+fn filter_by_attributes() {
+    let mut builder =
+        LogEntryFilterQueryBuilder::new(attributes_filter(r#" [meta ip="1"][origin x="2"] "#))
+            .expect("Cannot build attributes filter");
+
+    assert_eq!(
+        builder.to_sql_query().sql(),
+        "SELECT * FROM logs WHERE attributes @> $1::jsonb"
+    );
+}
+
+#[test]
+fn reject_attributes_filter_with_leftover_input() {
+    for attributes in [r#"[meta ip="1"] trailing"#, r#"[meta ip="1"]x"#, "- [meta]"] {
+        assert!(
+            LogEntryFilterQueryBuilder::new(attributes_filter(attributes)).is_err(),
+            "{attributes:?} was accepted"
+        );
+    }
+}
+
+fn attributes_filter(attributes: &str) -> LogEntryFilter {
+    LogEntryFilter {
+        time: None,
+        host: None,
+        severity: None,
+        facility: None,
+        syslog_tag: None,
+        source: None,
+        attributes: Some(attributes.into()),
+        trace_id: None,
+    }
+}
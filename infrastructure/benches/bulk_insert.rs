@@ -0,0 +1,89 @@
+use application::prelude::{DiskLogEntryDto, LogRepository};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use infrastructure::prelude::PgLogRepo;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// Compares storing the entries of a file one by one with the bulk insert of `create_logs`.
+///
+/// Run with `cargo bench -p infrastructure --bench bulk_insert`, a fresh database is created
+/// on the Postgres instance from `DATABASE_URL`.
+fn bulk_insert(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Cannot start Tokio runtime");
+    let log_repo = runtime.block_on(spawn_repo());
+    let mut group = c.benchmark_group("store_log_entries");
+    group.sample_size(10);
+
+    for count in [1_000, 10_000] {
+        let log_dtos = vec![log_dto(); count];
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("one_by_one", count),
+            &log_dtos,
+            |b, log_dtos| {
+                b.to_async(&runtime).iter(|| async {
+                    for log_dto in log_dtos.clone() {
+                        log_repo
+                            .create_log(log_dto)
+                            .await
+                            .expect("Cannot add log entry to database");
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("bulk", count), &log_dtos, |b, log_dtos| {
+            b.to_async(&runtime).iter(|| async {
+                log_repo
+                    .create_logs(log_dtos.clone())
+                    .await
+                    .expect("Cannot add log entries to database")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn log_dto() -> DiskLogEntryDto {
+    DiskLogEntryDto {
+        facility: "user".into(),
+        host: "localhost".into(),
+        message: "Accepted publickey for user from 10.0.0.1 port 52213 ssh2".into(),
+        severity: "info".into(),
+        source: "sshd".into(),
+        syslog_tag: "sshd[4242]:".into(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    }
+}
+
+async fn spawn_repo() -> PgLogRepo {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL has to be set");
+    let database_name = Uuid::new_v4().to_string();
+
+    let mut connection = PgConnection::connect(&url)
+        .await
+        .expect("Failed to connect to Postgres instance");
+    connection
+        .execute(format!(r#"CREATE DATABASE "{database_name}";"#).as_str())
+        .await
+        .expect("Failed to create database");
+
+    let (server_url, _) = url.rsplit_once('/').expect("Invalid DATABASE_URL");
+    let pool = PgPool::connect(&format!("{server_url}/{database_name}"))
+        .await
+        .expect("Failed to connect to Postgres");
+
+    sqlx::migrate!("../migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    PgLogRepo::new(pool)
+}
+
+criterion_group!(benches, bulk_insert);
+criterion_main!(benches);
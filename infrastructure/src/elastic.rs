@@ -0,0 +1,283 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use application::prelude::DiskLogEntryDto;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::parsers::{severity_from_level, Priority};
+
+/// Elasticsearch version reported to the shippers, which pick their request format by it.
+const ELASTIC_VERSION: &str = "8.11.0";
+
+/// Header the Elasticsearch clients check to make sure they talk to Elasticsearch.
+pub const ELASTIC_PRODUCT_HEADER: (&str, &str) = ("X-Elastic-Product", "Elasticsearch");
+
+/// Document fields mapped onto the fields of the entries by the `_bulk` endpoint.
+///
+/// Each field lists dotted paths, e.g. `host.name`, which match either nested objects or
+/// keys containing the dots. The first path holding a string, a number or a boolean is
+/// used and removed from the document, the rest of the document is kept in the attributes.
+/// The defaults follow the Elastic Common Schema used by Filebeat and Logstash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElasticFieldMap {
+    /// RFC 3339 timestamp or milliseconds since the epoch, the time of arrival if missing.
+    pub timestamp: Vec<String>,
+    /// The whole document is the message if none of the fields is present.
+    pub message: Vec<String>,
+    pub host: Vec<String>,
+    /// Level name, e.g. `WARN` or `error`, mapped onto the syslog severity.
+    pub severity: Vec<String>,
+    /// Name of the index if none of the fields is present.
+    pub source: Vec<String>,
+    pub app_name: Vec<String>,
+    pub proc_id: Vec<String>,
+    pub trace_id: Vec<String>,
+    pub span_id: Vec<String>,
+}
+
+impl Default for ElasticFieldMap {
+    fn default() -> Self {
+        let paths = |paths: &[&str]| paths.iter().map(|&path| path.into()).collect();
+
+        ElasticFieldMap {
+            timestamp: paths(&["@timestamp", "timestamp"]),
+            message: paths(&["message"]),
+            host: paths(&["host.name", "host.hostname", "host"]),
+            severity: paths(&["log.level", "level", "severity"]),
+            source: paths(&["service.name", "event.dataset"]),
+            app_name: paths(&["process.name"]),
+            proc_id: paths(&["process.pid"]),
+            trace_id: paths(&["trace.id"]),
+            span_id: paths(&["span.id"]),
+        }
+    }
+}
+
+/// Action of a `_bulk` request with the entry decoded from its document.
+#[derive(Debug)]
+pub struct BulkItem {
+    /// Name of the action, `index`, `create`, `update` or `delete`.
+    pub action: String,
+    pub index: String,
+    pub log_entry: Result<DiskLogEntryDto>,
+}
+
+/// Decodes the actions of an Elasticsearch `_bulk` request, newline delimited JSON where
+/// each action line is followed by its document.
+///
+/// The `index` and `create` actions are decoded into entries with the field map. The
+/// entries can't be changed once stored, so `update` and `delete` are only reported as
+/// failed items, as are the documents which can't be decoded. A malformed action line
+/// or a missing document makes the whole request invalid, as it does for Elasticsearch.
+pub fn decode_bulk(
+    body: &[u8],
+    default_index: Option<&str>,
+    fields: &ElasticFieldMap,
+) -> Result<Vec<BulkItem>> {
+    let body = std::str::from_utf8(body)?;
+    let mut lines = body.lines().filter(|line| !line.trim().is_empty());
+    let mut items = Vec::new();
+
+    while let Some(line) = lines.next() {
+        let (action, metadata) = match serde_json::from_str(line)? {
+            Value::Object(action) if action.len() == 1 => {
+                action.into_iter().next().expect("Action has one key")
+            }
+            _ => bail!("Malformed action line {line:?}, expected an object with one key"),
+        };
+
+        let index = metadata
+            .get("_index")
+            .and_then(Value::as_str)
+            .or(default_index)
+            .unwrap_or_default()
+            .to_owned();
+
+        let document = match action.as_str() {
+            "index" | "create" | "update" => lines
+                .next()
+                .ok_or_else(|| anyhow!("Action {action:?} isn't followed by a document"))?,
+            "delete" => "",
+            _ => bail!("Unknown action {action:?}"),
+        };
+
+        let log_entry = match action.as_str() {
+            _ if index.is_empty() => Err(anyhow!("Index is missing")),
+            "index" | "create" => serde_json::from_str(document)
+                .context("Document isn't valid JSON")
+                .and_then(|document| to_log_entry(document, &index, fields)),
+            _ => Err(anyhow!(
+                "Action {action:?} isn't supported, stored entries can't be changed"
+            )),
+        };
+
+        items.push(BulkItem {
+            action,
+            index,
+            log_entry,
+        });
+    }
+
+    Ok(items)
+}
+
+/// Elasticsearch shaped response to a `_bulk` request, `ids` being the ids of the stored
+/// entries in the order of the successful items. Items dropped by the processors have the nil
+/// id, they're reported as `noop`.
+pub fn bulk_response(items: &[BulkItem], ids: &[Uuid], took: Duration) -> Value {
+    let mut ids = ids.iter();
+
+    let results: Vec<_> = items
+        .iter()
+        .map(|item| {
+            let result = match &item.log_entry {
+                Ok(_) => match ids.next().expect("Every stored item has an id") {
+                    id if id.is_nil() => json!({
+                        "_index": item.index,
+                        "result": "noop",
+                        "_shards": { "total": 1, "successful": 0, "failed": 0 },
+                        "status": 200,
+                    }),
+                    id => json!({
+                        "_index": item.index,
+                        "_id": id,
+                        "_version": 1,
+                        "result": "created",
+                        "_shards": { "total": 1, "successful": 1, "failed": 0 },
+                        "status": 201,
+                    }),
+                },
+                Err(e) => json!({
+                    "_index": item.index,
+                    "status": 400,
+                    "error": {
+                        "type": match item.action.as_str() {
+                            "index" | "create" => "document_parsing_exception",
+                            _ => "illegal_argument_exception",
+                        },
+                        "reason": format!("{e:#}"),
+                    },
+                }),
+            };
+
+            json!({ item.action.clone(): result })
+        })
+        .collect();
+
+    json!({
+        "took": took.as_millis() as u64,
+        "errors": items.iter().any(|item| item.log_entry.is_err()),
+        "items": results,
+    })
+}
+
+/// Response to `GET /`, which the shippers use to check the version of Elasticsearch.
+pub fn cluster_info() -> Value {
+    json!({
+        "name": "ferri-log",
+        "cluster_name": "ferri-log",
+        "version": {
+            "number": ELASTIC_VERSION,
+            "build_flavor": "default",
+            "minimum_wire_compatibility_version": "7.17.0",
+            "minimum_index_compatibility_version": "7.0.0",
+        },
+        "tagline": "You Know, for Search",
+    })
+}
+
+fn to_log_entry(document: Value, index: &str, fields: &ElasticFieldMap) -> Result<DiskLogEntryDto> {
+    let mut document = match document {
+        Value::Object(document) => document,
+        _ => bail!("Document isn't an object"),
+    };
+
+    let timestamp = match take_first(&mut document, &fields.timestamp) {
+        Some(timestamp) => parse_timestamp(&timestamp)?,
+        None => Utc::now(),
+    };
+
+    let message = match take_first(&mut document, &fields.message) {
+        Some(message) => message,
+        None => Value::Object(document.clone()).to_string(),
+    };
+
+    let severity = take_first(&mut document, &fields.severity)
+        .as_deref()
+        .and_then(severity_from_level)
+        .unwrap_or(Priority::DEFAULT.severity_name());
+
+    let source = take_first(&mut document, &fields.source).unwrap_or_else(|| index.into());
+
+    Ok(DiskLogEntryDto {
+        timestamp: timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        host: take_first(&mut document, &fields.host).unwrap_or_default(),
+        severity: severity.into(),
+        facility: Priority::DEFAULT.facility_name().into(),
+        syslog_tag: format!("{source}:"),
+        source,
+        message,
+        app_name: take_first(&mut document, &fields.app_name),
+        proc_id: take_first(&mut document, &fields.proc_id),
+        trace_id: take_first(&mut document, &fields.trace_id),
+        span_id: take_first(&mut document, &fields.span_id),
+        attributes: document,
+        ..Default::default()
+    })
+}
+
+/// RFC 3339 timestamp or milliseconds since the epoch, the default date format of Elasticsearch.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>> {
+    if let Ok(millis) = timestamp.parse::<i64>() {
+        return Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .ok_or_else(|| anyhow!("Timestamp {millis} is out of range"));
+    }
+
+    Ok(DateTime::parse_from_rfc3339(timestamp)
+        .with_context(|| format!("Invalid timestamp {timestamp:?}"))?
+        .with_timezone(&Utc))
+}
+
+/// Takes the value of the first of the paths holding a scalar out of the document.
+fn take_first(document: &mut Map<String, Value>, paths: &[String]) -> Option<String> {
+    let value = paths.iter().find_map(|path| take_scalar(document, path))?;
+
+    match value {
+        Value::String(value) => Some(value),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Takes the scalar at the dotted path out of the document, removing the objects
+/// left empty. A key containing dots is matched before the nested objects.
+fn take_scalar(document: &mut Map<String, Value>, path: &str) -> Option<Value> {
+    if document.get(path).is_some_and(is_scalar) {
+        return document.remove(path);
+    }
+
+    for (i, _) in path.match_indices('.') {
+        let (parent, child) = (&path[..i], &path[i + 1..]);
+
+        if let Some(Value::Object(object)) = document.get_mut(parent) {
+            if let Some(value) = take_scalar(object, child) {
+                if object.is_empty() {
+                    document.remove(parent);
+                }
+
+                return Some(value);
+            }
+        }
+    }
+
+    None
+}
+
+fn is_scalar(value: &Value) -> bool {
+    matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_))
+}
@@ -0,0 +1,32 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::Result;
+use flate2::read::MultiGzDecoder;
+
+/// Compression of a rotated log file, recognized by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn decoder(self, file: File) -> Result<Box<dyn BufRead + Send>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+            Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+        })
+    }
+}
@@ -1,30 +1,164 @@
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fs::File,
-    io::{Read, Seek, SeekFrom},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
 };
 
 use anyhow::{anyhow, bail, Result};
-use application::prelude::{Cache, DiskLogEntryDto, FileSystem, LogRepository};
+use application::prelude::{
+    Cache, DiskLogEntryDto, FileSystem, LogRepository, OffsetRepository, RejectedLineDto,
+};
 use async_trait::async_trait;
-use notify::{Event, EventKind};
+use domain::prelude::FileOffset;
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Event, EventKind,
+};
 use skytable::{error::Error::SkyError, error::SkyhashError, RespCode};
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
+
+use super::{archive::Compression, source::Source};
+use crate::parsers::{parse_line, Framing, MAX_LINE_BYTES};
+
+/// Number of lines read from a file between saving the progress.
+const BATCH_LINES: usize = 10_000;
+
+/// Number of bytes after which a batch is stored even if it has fewer lines, so that
+/// a file is never held in memory as a whole.
+const BATCH_BYTES: usize = 4 * 1024 * 1024;
+
+/// Number of files caught up between the progress reports of a rescan.
+const RESCAN_PROGRESS_FILES: usize = 100;
 
-pub struct LinuxFS<T: Cache, L: LogRepository> {
-    cache: T,
+pub struct LinuxFS<T: Cache, L: LogRepository + OffsetRepository> {
+    /// Skytable store of the offsets saved before they were moved to Postgres,
+    /// only read for the files without an offset in the database.
+    cache: Option<T>,
     log_repo: L,
+    source: Source,
+    /// Files are taken out while they are read, so that different files are handled
+    /// concurrently. Events of one file have to be handled in order.
+    files: std::sync::Mutex<HashMap<String, TrackedFile>>,
+    /// Tracker of the last `RenameMode::From` event, its `RenameMode::To` counterpart
+    /// is skipped as the rename is handled once the `RenameMode::Both` event arrives.
+    rename_tracker: std::sync::Mutex<Option<usize>>,
+    /// Number of stored lines which weren't valid UTF-8, the invalid sequences are
+    /// replaced by `U+FFFD`.
+    invalid_utf8_lines: AtomicU64,
+}
+
+/// Position of the tailer in a file.
+///
+/// The device and inode numbers identify the file, so that a rotated file
+/// isn't mistaken for the one which replaced it under the same path.
+/// For compressed files the offset counts decompressed bytes and `complete`
+/// marks an archive which was read to the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FilePosition {
+    dev: u64,
+    ino: u64,
+    offset: u64,
+    complete: bool,
+}
+
+impl FilePosition {
+    fn new(dev: u64, ino: u64, offset: u64) -> Self {
+        FilePosition {
+            dev,
+            ino,
+            offset,
+            complete: false,
+        }
+    }
+
+    fn from_file_offset(offset: &FileOffset) -> Self {
+        FilePosition {
+            dev: offset.dev as u64,
+            ino: offset.ino as u64,
+            offset: offset.byte_offset as u64,
+            complete: offset.complete,
+        }
+    }
+
+    fn to_file_offset(self, path: &str) -> FileOffset {
+        FileOffset {
+            path: path.into(),
+            dev: self.dev as i64,
+            ino: self.ino as i64,
+            byte_offset: self.offset as i64,
+            complete: self.complete,
+        }
+    }
+
+    fn from_cache_value(value: &str) -> Result<Self> {
+        match value.split(':').collect::<Vec<_>>()[..] {
+            // Offsets stored before the file identity was tracked
+            [offset] => Ok(FilePosition::new(0, 0, offset.parse()?)),
+            [dev, ino, offset] => Ok(FilePosition::new(
+                dev.parse()?,
+                ino.parse()?,
+                offset.parse()?,
+            )),
+            [dev, ino, offset, complete] => Ok(FilePosition {
+                complete: complete == "1",
+                ..FilePosition::new(dev.parse()?, ino.parse()?, offset.parse()?)
+            }),
+            _ => bail!("Invalid file position '{value}'"),
+        }
+    }
+
+    fn is_same_file(&self, dev: u64, ino: u64) -> bool {
+        (self.dev == 0 && self.ino == 0) || (self.dev == dev && self.ino == ino)
+    }
+}
+
+/// File kept open between events, so that it can be read to the end even after it was
+/// rotated (renamed or deleted) and its path already belongs to another file.
+struct TrackedFile {
+    file: File,
+    position: FilePosition,
+    pending: Option<PendingEvent>,
+}
+
+/// Multi-line event at the end of a file waiting for its continuation, the position of
+/// the file stays at its start until it's stored.
+struct PendingEvent {
+    /// End of the file when the event last grew.
+    end: u64,
+    since: Instant,
+}
+
+impl TrackedFile {
+    fn open(path: &Path, offset: u64) -> Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+
+        Ok(TrackedFile {
+            file,
+            position: FilePosition::new(metadata.dev(), metadata.ino(), offset),
+            pending: None,
+        })
+    }
 }
 
 impl<T, L> LinuxFS<T, L>
 where
     T: Cache,
-    L: LogRepository,
+    L: LogRepository + OffsetRepository,
 {
-    pub fn new(cache: T, repo: L) -> Self {
+    pub fn new(cache: Option<T>, repo: L, source: Source) -> Self {
         LinuxFS {
             cache,
             log_repo: repo,
+            source,
+            files: std::sync::Mutex::new(HashMap::new()),
+            rename_tracker: std::sync::Mutex::new(None),
+            invalid_utf8_lines: AtomicU64::new(0),
         }
     }
 
@@ -36,10 +170,80 @@ where
         Ok(())
     }
 
-    fn on_files_delete(&self, paths: Vec<PathBuf>) -> Result<()> {
+    /// Reads what's left in the deleted files before forgetting about them.
+    async fn on_files_delete(&self, paths: Vec<PathBuf>) -> Result<()> {
         for path in paths {
-            let path = path_buff_to_string(&path)?;
-            self.cache.del(&path)?;
+            let key = path_buff_to_string(&path)?;
+
+            if let Some(mut tracked) = self.untrack(&key) {
+                self.read_new_lines(&path, &mut tracked, true).await?;
+            }
+
+            self.forget_position(&key).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles a file moved away from `from`, either to `to` within the watched folder
+    /// or outside of it.
+    ///
+    /// Lines written before the rename are read from the still open file. The file keeps
+    /// being tracked under `from` until another file shows up there. When the destination
+    /// is known the file is closed once read, its position is kept under `to` so that it's
+    /// opened again if it changes. A destination excluded from the source is read to its
+    /// end and forgotten.
+    async fn on_file_rename(&self, from: &Path, to: Option<&Path>) -> Result<()> {
+        let from_key = path_buff_to_string(from)?;
+        let excluded = to.is_some_and(|to| !self.source.matches(to));
+
+        let tracked = match self.untrack(&from_key) {
+            Some(mut tracked) => {
+                self.read_new_lines(from, &mut tracked, excluded).await?;
+                Some(tracked)
+            }
+            None => None,
+        };
+
+        let to = match to {
+            Some(to) => to,
+            None => {
+                if let Some(tracked) = tracked {
+                    self.track(from_key, tracked);
+                }
+
+                return Ok(());
+            }
+        };
+
+        let to_key = path_buff_to_string(to)?;
+
+        if excluded {
+            info!("{from_key} was renamed to {to_key}, which isn't part of the source");
+            return self.forget_position(&from_key).await;
+        }
+
+        info!("{from_key} was renamed to {to_key}");
+
+        let position = match tracked {
+            Some(tracked) => {
+                let position = tracked.position;
+
+                // The event waiting for its continuation is only flushed while tracked
+                if tracked.pending.is_some() {
+                    self.track(to_key.clone(), tracked);
+                }
+
+                Some(position)
+            }
+            None => self.restore_position(&from_key).await?,
+        };
+
+        if let Some(position) = position {
+            self.log_repo
+                .set_offset(position.to_file_offset(&to_key))
+                .await?;
+            self.forget_position(&from_key).await?;
         }
 
         Ok(())
@@ -52,38 +256,385 @@ where
             return Ok(());
         }
 
-        let path = path_buff_to_string(&path)?;
-        let mut file = File::open(&path)?;
-        let mut buffer = String::new();
+        if !self.source.matches(&path) {
+            debug!("{} isn't included in the source", path.display());
+            return Ok(());
+        }
+
+        if let Some(compression) = Compression::detect(&path) {
+            return self.handle_archive_change(&path, compression).await;
+        }
+
+        let key = path_buff_to_string(&path)?;
+        let metadata = std::fs::metadata(&path)?;
 
-        let to_skip = match self.cache.get::<u64>(&path) {
-            Ok(v) => v,
-            Err(SkyError(SkyhashError::Code(RespCode::NotFound))) => {
-                self.cache.set(&path, "0")?;
-                u64::default()
+        let mut tracked = match self.untrack(&key) {
+            Some(tracked)
+                if tracked
+                    .position
+                    .is_same_file(metadata.dev(), metadata.ino()) =>
+            {
+                tracked
+            }
+            Some(mut rotated) => {
+                info!("{key} was rotated, finishing the previous file first");
+                self.read_new_lines(&path, &mut rotated, false).await?;
+                TrackedFile::open(&path, 0)?
             }
-            Err(e) => return Err(anyhow!(e)),
+            None => match self.restore_position(&key).await? {
+                Some(position) if position.is_same_file(metadata.dev(), metadata.ino()) => {
+                    TrackedFile::open(&path, position.offset)?
+                }
+                Some(_) => {
+                    warn!("{key} was replaced while it wasn't tracked, reading the new file from the beginning");
+                    TrackedFile::open(&path, 0)?
+                }
+                None => TrackedFile::open(&path, 0)?,
+            },
         };
 
-        debug!("To skip: {to_skip}");
+        let size = tracked.file.metadata()?.len();
 
-        file.seek(SeekFrom::Start(to_skip))?;
-        let n = file.read_to_string(&mut buffer)? as u64;
+        if size < tracked.position.offset {
+            info!(
+                "{key} was truncated from {} to {size} bytes, reading from the beginning",
+                tracked.position.offset
+            );
+            tracked.position.offset = 0;
+        }
 
-        debug!("Readed {n}: {buffer:?}");
+        debug!("To skip: {}", tracked.position.offset);
 
-        self.process_log_entry(buffer).await?;
-        self.cache.update(&path, (to_skip + n).to_string())?;
+        let result = self.read_new_lines(&path, &mut tracked, false).await;
+        self.track(key, tracked);
 
-        Ok(())
+        result
+    }
+
+    fn track(&self, key: String, tracked: TrackedFile) {
+        self.files.lock().unwrap().insert(key, tracked);
+    }
+
+    fn untrack(&self, key: &str) -> Option<TrackedFile> {
+        self.files.lock().unwrap().remove(key)
+    }
+
+    /// Reads a compressed file once.
+    ///
+    /// The archive is decompressed in batches and the progress is saved after each of them,
+    /// so that an interrupted ingestion resumes where it stopped. An archive which is still
+    /// being written can't be decompressed to the end, its complete lines are ingested and
+    /// the rest is read on one of the next changes.
+    #[instrument(skip_all)]
+    async fn handle_archive_change(&self, path: &Path, compression: Compression) -> Result<()> {
+        let key = path_buff_to_string(path)?;
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+
+        let mut position = match self.restore_position(&key).await? {
+            Some(position) if position.is_same_file(metadata.dev(), metadata.ino()) => position,
+            _ => FilePosition::new(metadata.dev(), metadata.ino(), 0),
+        };
+
+        if position.complete {
+            debug!("{key} was already ingested");
+            return Ok(());
+        }
+
+        let framing = self.source.parsers().framing(path);
+        let mut reader = compression.decoder(file)?;
+        io::copy(&mut reader.by_ref().take(position.offset), &mut io::sink())?;
+        let mut incomplete = Vec::new();
+
+        loop {
+            let mut batch = std::mem::take(&mut incomplete);
+            let end = read_batch(&mut reader, &mut batch, framing);
+            position.complete = matches!(end, BatchEnd::Eof);
+
+            if !position.complete {
+                incomplete = batch.split_off(framing.complete_len(&batch));
+            }
+
+            let stored = self
+                .store_log_entries(path, &batch, position, position.complete)
+                .await?;
+            incomplete.splice(
+                ..0,
+                batch.drain((stored.offset - position.offset) as usize..),
+            );
+            position = stored;
+
+            if let BatchEnd::Failed(e) = end {
+                info!("{key} cannot be decompressed to the end yet ({e}), continuing on the next change");
+                return Ok(());
+            }
+
+            if position.complete {
+                info!("{key} was ingested");
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads the lines appended since the last read in batches and moves the position
+    /// past each batch once it is stored.
+    ///
+    /// A line which isn't terminated by a newline yet is probably still being written,
+    /// it's left for the next read unless `until_eof` is set. The same goes for the other
+    /// records, e.g. journal entries, which are carried over to the next batch when the
+    /// batch ends in the middle of one.
+    ///
+    /// So is the last multi-line event of the file, which may still be continued, until
+    /// no line was added to it for the flush timeout of the source.
+    async fn read_new_lines(
+        &self,
+        path: &Path,
+        tracked: &mut TrackedFile,
+        until_eof: bool,
+    ) -> Result<()> {
+        tracked
+            .file
+            .seek(SeekFrom::Start(tracked.position.offset))?;
+        let framing = self.source.parsers().framing(path);
+        let mut reader = BufReader::new(&tracked.file);
+        let mut incomplete = Vec::new();
+
+        loop {
+            let mut batch = std::mem::take(&mut incomplete);
+            let eof = match read_batch(&mut reader, &mut batch, framing) {
+                BatchEnd::Full => false,
+                BatchEnd::Eof => true,
+                BatchEnd::Failed(e) => return Err(e.into()),
+            };
+
+            let complete = framing.complete_len(&batch);
+
+            if !eof {
+                incomplete = batch.split_off(complete);
+            } else if !until_eof {
+                batch.truncate(complete);
+            }
+
+            debug!("Read {} bytes from {}", batch.len(), path.display());
+
+            let end = tracked.position.offset + batch.len() as u64;
+            let flush = eof
+                && (until_eof
+                    || tracked.pending.as_ref().is_some_and(|pending| {
+                        pending.end == end && self.pending_expired(pending)
+                    }));
+
+            if !batch.is_empty() {
+                let stored = self
+                    .store_log_entries(path, &batch, tracked.position, flush)
+                    .await?;
+                incomplete.splice(
+                    ..0,
+                    batch.drain((stored.offset - tracked.position.offset) as usize..),
+                );
+                tracked.position = stored;
+            }
+
+            if eof {
+                tracked.pending = match tracked.pending.take() {
+                    _ if tracked.position.offset == end => None,
+                    Some(pending) if pending.end == end => Some(pending),
+                    _ => Some(PendingEvent {
+                        end,
+                        since: Instant::now(),
+                    }),
+                };
+
+                return Ok(());
+            }
+        }
     }
 
-    async fn process_log_entry(&self, buff: String) -> Result<()> {
-        for line in buff.lines() {
+    fn pending_expired(&self, pending: &PendingEvent) -> bool {
+        self.source
+            .multiline()
+            .is_some_and(|multiline| pending.since.elapsed() >= multiline.flush_timeout())
+    }
+
+    /// Parses the lines of `buff`, read from `position`, and stores them together with
+    /// the position they were read up to, which is returned.
+    ///
+    /// Lines which can't be parsed are stored as rejected, with the configuration of the
+    /// parser, so that they can be retried later without holding up the rest of the file.
+    ///
+    /// Lines continuing a multi-line event are joined into its message. The last event may
+    /// still be continued, it's only stored with `flush`. Otherwise the returned position
+    /// is its start, so that it's read again with its continuation.
+    async fn store_log_entries(
+        &self,
+        path: &Path,
+        buff: &[u8],
+        mut position: FilePosition,
+        flush: bool,
+    ) -> Result<FilePosition> {
+        let key = path_buff_to_string(path)?;
+        let parser = self.source.parsers().select(path);
+        let framing = self.source.parsers().framing(path);
+        let multiline = self.source.multiline();
+        let mut log_entries = Vec::new();
+        let mut rejected_lines = Vec::new();
+        let mut invalid_utf8_lines = 0;
+        let mut offset = position.offset;
+        // Start offset, entry and number of lines of the multi-line event being joined
+        let mut event: Option<(u64, DiskLogEntryDto, usize)> = None;
+        // Whether the previous record was cut at the size limit, the record is its rest
+        let mut oversize = false;
+
+        for raw_line in framing.records(buff) {
+            let line_offset = offset;
+            offset += raw_line.len() as u64;
+
+            let rest_of_oversize = std::mem::replace(&mut oversize, framing.is_oversize(raw_line));
+            let decoded = match framing.decode(raw_line) {
+                Ok(_) if rest_of_oversize => Err(anyhow!("Rest of a record over the size limit")),
+                decoded => decoded,
+            };
+
+            let line = match decoded {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Cannot decode record of {key} at {line_offset}: {:?}", e);
+                    log_entries.extend(event.take().map(|(_, log_entry, _)| log_entry));
+                    let raw = String::from_utf8_lossy(raw_line).into_owned();
+                    rejected_lines.push(self.rejected_line(&key, path, line_offset, raw, &e)?);
+                    continue;
+                }
+            };
+
+            if let (Framing::Lines, Cow::Owned(_)) = (framing, &line) {
+                warn!("Line of {key} at {line_offset} isn't valid UTF-8");
+                invalid_utf8_lines += 1;
+            }
+
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
             debug!("Processing the following file contetn: {line}");
-            let log_entry = serde_json::from_str::<DiskLogEntryDto>(line)?;
-            self.log_repo.create_log(log_entry).await?;
+
+            let parsed = parse_line(parser, line, self.source.timestamps());
+
+            if let (Some(multiline), Some((_, log_entry, lines))) = (multiline, event.as_mut()) {
+                let text = match &parsed {
+                    Ok(continuation) => continuation.message.as_str(),
+                    Err(_) => line,
+                };
+
+                if multiline.continues(text, *lines, log_entry.message.len()) {
+                    log_entry.message.push('\n');
+                    log_entry.message.push_str(text);
+                    *lines += 1;
+                    continue;
+                }
+            }
+
+            log_entries.extend(event.take().map(|(_, log_entry, _)| log_entry));
+
+            match parsed {
+                Ok(mut log_entry) => {
+                    self.source.apply(path, &mut log_entry);
+
+                    if multiline.is_some() {
+                        event = Some((line_offset, log_entry, 1));
+                    } else {
+                        log_entries.push(log_entry);
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Cannot parse line {line:?} of {key} at {line_offset}: {:?}",
+                        e
+                    );
+                    rejected_lines.push(self.rejected_line(
+                        &key,
+                        path,
+                        line_offset,
+                        line.into(),
+                        &e,
+                    )?);
+                }
+            }
+        }
+
+        position.offset = offset;
+
+        if let Some((event_offset, log_entry, _)) = event {
+            if flush {
+                log_entries.push(log_entry);
+            } else {
+                position.offset = event_offset;
+                position.complete = false;
+            }
+        }
+
+        let count = log_entries.len();
+        let started = Instant::now();
+
+        self.log_repo
+            .create_logs_with_offset(log_entries, rejected_lines, position.to_file_offset(&key))
+            .await?;
+        self.invalid_utf8_lines
+            .fetch_add(invalid_utf8_lines, Ordering::Relaxed);
+
+        if count > 0 {
+            let elapsed = started.elapsed();
+            info!(
+                "Stored {count} log entries in {elapsed:?} ({:.0} entries/s)",
+                count as f64 / elapsed.as_secs_f64()
+            );
+        }
+
+        Ok(position)
+    }
+
+    fn rejected_line(
+        &self,
+        key: &str,
+        path: &Path,
+        offset: u64,
+        raw: String,
+        error: &anyhow::Error,
+    ) -> Result<RejectedLineDto> {
+        Ok(RejectedLineDto {
+            path: key.into(),
+            byte_offset: offset as i64,
+            raw,
+            error: format!("{error:#}"),
+            parser: serde_json::to_value(self.source.parsers().config(path))?,
+        })
+    }
+
+    async fn restore_position(&self, key: &str) -> Result<Option<FilePosition>> {
+        if let Some(offset) = self.log_repo.get_offset(key).await? {
+            return Ok(Some(FilePosition::from_file_offset(&offset)));
+        }
+
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+
+        match cache.get::<String>(key) {
+            Ok(value) => Ok(Some(FilePosition::from_cache_value(&value)?)),
+            Err(SkyError(SkyhashError::Code(RespCode::NotFound))) => Ok(None),
+            Err(e) => Err(anyhow!(e)),
+        }
+    }
+
+    async fn forget_position(&self, key: &str) -> Result<()> {
+        self.log_repo.delete_offset(key).await?;
+
+        if let Some(cache) = &self.cache {
+            cache.del(key)?;
         }
+
         Ok(())
     }
 }
@@ -92,17 +643,165 @@ where
 impl<T, L> FileSystem for LinuxFS<T, L>
 where
     T: Cache + Send + Sync,
-    L: LogRepository + Send + Sync,
+    L: LogRepository + OffsetRepository + Send + Sync,
 {
     async fn handle_event(&self, event: Event) -> Result<()> {
         info!("Handling evet: {event:?}");
 
         match event.kind {
-            EventKind::Modify(_) => self.on_files_modification(event.paths).await,
-            EventKind::Remove(_) => self.on_files_delete(event.paths),
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                *self.rename_tracker.lock().unwrap() = event.tracker();
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To))
+                if event.tracker().is_some()
+                    && *self.rename_tracker.lock().unwrap() == event.tracker() =>
+            {
+                debug!("Waiting for the rename to be paired");
+                return Ok(());
+            }
+            _ => (),
+        }
+
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match &event.paths[..] {
+                [from, to] => self.on_file_rename(from, Some(to)).await,
+                _ => Ok(()),
+            },
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in &event.paths {
+                    self.on_file_rename(path, None).await?;
+                }
+
+                Ok(())
+            }
+            // The polling watcher reports a new file only by its creation
+            EventKind::Create(_) | EventKind::Modify(_) => {
+                self.on_files_modification(event.paths).await
+            }
+            EventKind::Remove(_) => self.on_files_delete(event.paths).await,
             _ => Ok(()),
         }
     }
+
+    fn expired_events(&self) -> Vec<PathBuf> {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, tracked)| {
+                tracked
+                    .pending
+                    .as_ref()
+                    .is_some_and(|pending| self.pending_expired(pending))
+            })
+            .map(|(key, _)| PathBuf::from(key))
+            .collect()
+    }
+
+    fn invalid_utf8_lines(&self) -> u64 {
+        self.invalid_utf8_lines.load(Ordering::Relaxed)
+    }
+
+    async fn rescan(&self, root: &Path) -> Result<()> {
+        let started = Instant::now();
+        let paths: Vec<_> = list_files(root)?
+            .into_iter()
+            .filter(|path| self.source.matches(path))
+            .collect();
+        let total = paths.len();
+
+        info!("Rescanning {total} files in {}", root.display());
+
+        for (idx, path) in paths.into_iter().enumerate() {
+            debug!("Catching up {} ({}/{total})", path.display(), idx + 1);
+
+            if let Err(e) = self.handle_file_change(path.clone()).await {
+                error!("Cannot catch up {}: {:?}", path.display(), e);
+            }
+
+            if (idx + 1) % RESCAN_PROGRESS_FILES == 0 {
+                info!("Rescanned {}/{total} files", idx + 1);
+            }
+        }
+
+        info!(
+            "Rescan of {} finished in {:?}",
+            root.display(),
+            started.elapsed()
+        );
+
+        Ok(())
+    }
+}
+
+/// Lists the files under `root` recursively, in a stable order.
+fn list_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// How reading of a batch ended.
+enum BatchEnd {
+    /// The batch reached its size, the file may continue.
+    Full,
+    Eof,
+    /// The file couldn't be read further, the batch holds the lines read before.
+    Failed(io::Error),
+}
+
+/// Reads whole lines into `batch` until it has `BATCH_LINES` lines or `BATCH_BYTES` bytes.
+///
+/// Lines are read in pieces of at most `MAX_LINE_BYTES`, so that a line without end
+/// doesn't exhaust the memory.
+///
+/// The batch starts with the bytes carried over from the previous one, e.g. a journal entry
+/// or a multi-line event larger than a batch. Reading goes on past the limits until a record
+/// ends after the carried over ones, so that every batch makes progress.
+fn read_batch(reader: &mut impl BufRead, batch: &mut Vec<u8>, framing: Framing) -> BatchEnd {
+    let carried = framing.complete_len(batch);
+    let mut lines = 0;
+    // Past the limits the records are looked for each time the batch doubles, so that
+    // a large record isn't scanned, nor a large event parsed again, after every line
+    let mut next_check = 2 * batch.len();
+
+    loop {
+        if (lines >= BATCH_LINES || batch.len() >= BATCH_BYTES) && batch.len() >= next_check {
+            if framing.complete_len(batch) > carried {
+                return BatchEnd::Full;
+            }
+
+            next_check = batch.len() * 2;
+        }
+
+        let batch_len = batch.len();
+        let mut line = reader.by_ref().take(MAX_LINE_BYTES as u64);
+
+        match line.read_until(b'\n', batch) {
+            Ok(0) => return BatchEnd::Eof,
+            Ok(_) => lines += 1,
+            Err(e) => {
+                batch.truncate(batch_len);
+                return BatchEnd::Failed(e);
+            }
+        }
+    }
 }
 
 fn path_buff_to_string(path: &Path) -> Result<String> {
@@ -1,5 +1,14 @@
+mod archive;
 mod fs;
+mod multiline;
+mod pipeline;
+mod source;
 mod watcher;
 
 pub use fs::LinuxFS;
-pub use watcher::watch_dir;
+pub use multiline::MultilineConfig;
+pub use pipeline::{
+    event_pipeline, EventPipeline, EventSender, PipelineStats, PipelineStatsSnapshot,
+};
+pub use source::{ParserRule, Source, SourceConfig};
+pub use watcher::{watch_dir, WatchHandle};
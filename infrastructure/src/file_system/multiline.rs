@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Rules joining the lines of one event, e.g. a stack trace, into a single entry.
+///
+/// A line continues the previous event when it doesn't match `start`, matches
+/// `continuation` or, with `indented`, starts with whitespace. The rules are matched
+/// against the message of the parsed line, or the raw line when it can't be parsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultilineConfig {
+    /// Regex of the first line of an event, e.g. `^\d{4}-\d{2}-\d{2}`.
+    pub start: Option<String>,
+    /// Regex of the lines continuing the previous event, e.g. `^(\s+at |Caused by:)`.
+    pub continuation: Option<String>,
+    #[serde(default)]
+    pub indented: bool,
+    /// The last event of a file is stored once no line continued it for this long.
+    #[serde(default = "default_flush_timeout_ms")]
+    pub flush_timeout_ms: u64,
+    /// Events are cut after this many lines.
+    #[serde(default = "default_max_lines")]
+    pub max_lines: usize,
+    /// Events are cut before a line taking their message over this many bytes.
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+}
+
+fn default_flush_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_max_lines() -> usize {
+    500
+}
+
+fn default_max_bytes() -> usize {
+    1024 * 1024
+}
+
+pub(crate) struct Multiline {
+    start: Option<Regex>,
+    continuation: Option<Regex>,
+    indented: bool,
+    flush_timeout: Duration,
+    max_lines: usize,
+    max_bytes: usize,
+}
+
+impl Multiline {
+    pub(crate) fn new(config: &MultilineConfig) -> Result<Self> {
+        if config.start.is_none() && config.continuation.is_none() && !config.indented {
+            bail!("Multiline rules need a start or continuation pattern, or indentation");
+        }
+
+        Ok(Multiline {
+            start: config.start.as_deref().map(Regex::new).transpose()?,
+            continuation: config.continuation.as_deref().map(Regex::new).transpose()?,
+            indented: config.indented,
+            flush_timeout: Duration::from_millis(config.flush_timeout_ms),
+            max_lines: config.max_lines.max(1),
+            max_bytes: config.max_bytes,
+        })
+    }
+
+    /// Tells whether the line continues an event which already has `lines` lines
+    /// and a message of `bytes` bytes.
+    pub(crate) fn continues(&self, line: &str, lines: usize, bytes: usize) -> bool {
+        if lines >= self.max_lines || bytes + 1 + line.len() > self.max_bytes {
+            return false;
+        }
+
+        self.start
+            .as_ref()
+            .is_some_and(|start| !start.is_match(line))
+            || self
+                .continuation
+                .as_ref()
+                .is_some_and(|continuation| continuation.is_match(line))
+            || (self.indented && line.starts_with([' ', '\t']))
+    }
+
+    pub(crate) fn flush_timeout(&self) -> Duration {
+        self.flush_timeout
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use application::prelude::FileSystem;
+use notify::{
+    event::{DataChange, ModifyKind},
+    Event, EventKind,
+};
+use serde::Serialize;
+use tokio::sync::{mpsc, Notify};
+use tracing::{debug, error, info, warn};
+
+/// Number of events received from the watcher which wait for the dispatch.
+const EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// Number of workers handling the events of different files concurrently.
+const WORKERS: usize = 8;
+
+/// Number of events waiting for each worker.
+const WORKER_QUEUE_CAPACITY: usize = 64;
+
+/// Interval of the statistics reports in the log.
+const STATS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval of the checks for multi-line events waiting longer than their flush timeout.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+type EventResult = notify::Result<Event>;
+
+/// Counters of an event pipeline.
+#[derive(Debug, Default)]
+pub struct PipelineStats {
+    queued: AtomicUsize,
+    handled: AtomicU64,
+    coalesced: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Values of the pipeline counters at one moment.
+///
+/// `queued` is the number of events received and not handled yet, `coalesced` counts the
+/// modifications merged into one already waiting for the same file and `dropped` the events
+/// received after the pipeline stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PipelineStatsSnapshot {
+    pub queued: usize,
+    pub handled: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+    pub failed: u64,
+}
+
+impl PipelineStats {
+    pub fn snapshot(&self) -> PipelineStatsSnapshot {
+        PipelineStatsSnapshot {
+            queued: self.queued.load(Ordering::Relaxed),
+            handled: self.handled.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    fn finish(&self, counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Sending side of an event pipeline, passed to the watcher.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: mpsc::Sender<EventResult>,
+    stats: Arc<PipelineStats>,
+}
+
+impl EventSender {
+    /// Sends the event from a thread outside of the runtime, e.g. the one of the watcher,
+    /// waiting while the pipeline is full.
+    pub fn send_blocking(&self, event: EventResult) {
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+
+        if self.tx.blocking_send(event).is_err() {
+            self.on_closed();
+        }
+    }
+
+    pub async fn send(&self, event: Event) {
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+
+        if self.tx.send(Ok(event)).await.is_err() {
+            self.on_closed();
+        }
+    }
+
+    fn on_closed(&self) {
+        self.stats.queued.fetch_sub(1, Ordering::Relaxed);
+        self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+        warn!("Event pipeline is closed, dropping the event");
+    }
+}
+
+/// Pipeline handing the watcher events over to the file system.
+///
+/// Events of different files are handled concurrently by a fixed set of workers, the file
+/// decides the worker so that the events of one file are handled in order. A modification
+/// of a file which already waits for a worker is merged into the waiting one, as the file
+/// is read to its end anyway. Renames concern two files, they are handled once all the
+/// previous events were handled and before the next ones are dispatched.
+pub struct EventPipeline<T> {
+    fs: Arc<T>,
+    rx: mpsc::Receiver<EventResult>,
+    stats: Arc<PipelineStats>,
+}
+
+pub fn event_pipeline<T>(fs: Arc<T>) -> (EventSender, EventPipeline<T>) {
+    let (tx, rx) = mpsc::channel(EVENT_QUEUE_CAPACITY);
+    let stats = Arc::new(PipelineStats::default());
+
+    let sender = EventSender {
+        tx,
+        stats: stats.clone(),
+    };
+
+    (sender, EventPipeline { fs, rx, stats })
+}
+
+impl<T> EventPipeline<T>
+where
+    T: FileSystem + Send + Sync + 'static,
+{
+    pub fn stats(&self) -> Arc<PipelineStats> {
+        self.stats.clone()
+    }
+
+    /// Handles the events until all the senders are dropped.
+    pub async fn run(mut self) {
+        let dispatcher = Dispatcher::new(self.fs.clone(), self.stats.clone());
+        let mut interval = tokio::time::interval(STATS_INTERVAL);
+        let mut flush_interval = tokio::time::interval(FLUSH_INTERVAL);
+        let mut reported = (PipelineStatsSnapshot::default(), 0);
+
+        loop {
+            tokio::select! {
+                event = self.rx.recv() => match event {
+                    Some(Ok(event)) => dispatcher.dispatch(event).await,
+                    Some(Err(e)) => {
+                        error!("Watch error: {:?}", e);
+                        self.stats.finish(&self.stats.failed);
+                    }
+                    None => break,
+                },
+                _ = flush_interval.tick() => {
+                    // Read again like a modification, so that it's handled in order
+                    for path in self.fs.expired_events() {
+                        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+                        let event = Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Any)));
+                        dispatcher.dispatch(event.add_path(path)).await;
+                    }
+                }
+                _ = interval.tick() => {
+                    let stats = self.stats.snapshot();
+                    let invalid_utf8_lines = self.fs.invalid_utf8_lines();
+
+                    if (stats, invalid_utf8_lines) != reported {
+                        info!(
+                            "Event pipeline: {} queued, {} handled, {} coalesced, {} dropped, {} failed, \
+                             {} lines not valid UTF-8",
+                            stats.queued, stats.handled, stats.coalesced, stats.dropped, stats.failed,
+                            invalid_utf8_lines
+                        );
+                        reported = (stats, invalid_utf8_lines);
+                    }
+                }
+            }
+        }
+
+        dispatcher.flush().await;
+    }
+}
+
+struct Job {
+    event: Event,
+    coalescable: bool,
+}
+
+struct Dispatcher<T> {
+    fs: Arc<T>,
+    stats: Arc<PipelineStats>,
+    workers: Vec<mpsc::Sender<Job>>,
+    /// Files with a modification waiting for a worker.
+    pending: Arc<Mutex<HashSet<PathBuf>>>,
+    in_flight: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+impl<T> Dispatcher<T>
+where
+    T: FileSystem + Send + Sync + 'static,
+{
+    fn new(fs: Arc<T>, stats: Arc<PipelineStats>) -> Self {
+        let pending = Arc::new(Mutex::new(HashSet::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let idle = Arc::new(Notify::new());
+
+        let workers = (0..WORKERS)
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<Job>(WORKER_QUEUE_CAPACITY);
+                let fs = fs.clone();
+                let stats = stats.clone();
+                let pending = pending.clone();
+                let in_flight = in_flight.clone();
+                let idle = idle.clone();
+
+                tokio::task::spawn(async move {
+                    while let Some(job) = rx.recv().await {
+                        if job.coalescable {
+                            pending.lock().unwrap().remove(&job.event.paths[0]);
+                        }
+
+                        handle(&*fs, &stats, job.event).await;
+
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        idle.notify_waiters();
+                    }
+                });
+
+                tx
+            })
+            .collect();
+
+        Dispatcher {
+            fs,
+            stats,
+            workers,
+            pending,
+            in_flight,
+            idle,
+        }
+    }
+
+    async fn dispatch(&self, event: Event) {
+        if matches!(event.kind, EventKind::Modify(ModifyKind::Name(_))) || event.paths.is_empty() {
+            self.flush().await;
+            self.handle_alone(event).await;
+            return;
+        }
+
+        if event.paths.len() > 1 {
+            // One event per file, so that each goes to the worker of its file
+            self.stats
+                .queued
+                .fetch_add(event.paths.len() - 1, Ordering::Relaxed);
+
+            for path in &event.paths {
+                let mut single = event.clone();
+                single.paths = vec![path.clone()];
+                self.dispatch_single(single).await;
+            }
+        } else {
+            self.dispatch_single(event).await;
+        }
+    }
+
+    async fn dispatch_single(&self, event: Event) {
+        let path = &event.paths[0];
+        let coalescable = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Metadata(_))
+        );
+
+        {
+            let mut pending = self.pending.lock().unwrap();
+
+            if coalescable && !pending.insert(path.clone()) {
+                debug!("Coalescing event {event:?}");
+                self.stats.finish(&self.stats.coalesced);
+                return;
+            }
+
+            if !coalescable {
+                // The following modifications have to be handled after this event
+                pending.remove(path);
+            }
+        }
+
+        let worker = &self.workers[worker_index(path)];
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+
+        if worker.send(Job { event, coalescable }).await.is_err() {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.stats.finish(&self.stats.dropped);
+        }
+    }
+
+    async fn handle_alone(&self, event: Event) {
+        {
+            let mut pending = self.pending.lock().unwrap();
+
+            for path in &event.paths {
+                pending.remove(path);
+            }
+        }
+
+        handle(&*self.fs, &self.stats, event).await;
+    }
+
+    /// Waits until the workers handled all the dispatched events.
+    async fn flush(&self) {
+        loop {
+            let idle = self.idle.notified();
+
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+}
+
+async fn handle<T: FileSystem>(fs: &T, stats: &PipelineStats, event: Event) {
+    match fs.handle_event(event).await {
+        Ok(()) => stats.finish(&stats.handled),
+        Err(e) => {
+            error!("Handle event error: {:?}", e);
+            stats.finish(&stats.failed);
+        }
+    }
+}
+
+fn worker_index(path: &PathBuf) -> usize {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    hasher.finish() as usize % WORKERS
+}
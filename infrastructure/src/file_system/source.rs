@@ -0,0 +1,170 @@
+use std::{
+    path::{Component, Path},
+    time::Duration,
+};
+
+use anyhow::Result;
+use application::prelude::DiskLogEntryDto;
+use glob::Pattern;
+use notify::Config;
+use serde::{Deserialize, Serialize};
+
+use super::multiline::{Multiline, MultilineConfig};
+use crate::parsers::{ParserConfig, ParserSelector, TimestampNormalizer, MATCH_OPTIONS};
+
+/// Folder watched for log files, e.g. the `/var/log/remote/<host>/*.log` files written
+/// by rsyslog for remote hosts or the access logs in `/var/log/nginx`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    pub path: String,
+    /// Globs relative to `path` of the files to ingest, all files when empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs relative to `path` of the files to skip even though they are included.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Parser of the files matching none of the `parsers` rules.
+    #[serde(default)]
+    pub parser: ParserConfig,
+    #[serde(default)]
+    pub parsers: Vec<ParserRule>,
+    /// Host of the entries which don't carry one.
+    pub host: Option<String>,
+    /// Takes the host of the entries from the first directory under `path`.
+    #[serde(default)]
+    pub host_from_dir: bool,
+    /// Source of all the entries, instead of the one set by the parser.
+    pub source: Option<String>,
+    /// Polls the folder for changes every given number of milliseconds instead of relying
+    /// on inotify, which doesn't report changes made on other hosts of network filesystems.
+    ///
+    /// Renames are reported as a removal and a creation, so that a rotated file which stays
+    /// included is read again under its new name.
+    pub poll_interval_ms: Option<u64>,
+    /// Detects the changes of polled files by hashing their contents. Otherwise only their
+    /// modification time is compared, with one second precision, so that a write within
+    /// the same second as the previous poll is picked up with the next change.
+    #[serde(default)]
+    pub poll_compare_contents: bool,
+    /// Joins the lines of multi-line events, e.g. stack traces, into one entry.
+    pub multiline: Option<MultilineConfig>,
+    /// Timezone of the timestamps without an offset, e.g. BSD syslog ones: an IANA name
+    /// (`Europe/Berlin`), a fixed offset (`+02:00`) or `local`, the default.
+    pub timezone: Option<String>,
+    /// strftime formats of the timestamps, tried before the built-in ones,
+    /// e.g. `%d.%m.%Y %H:%M:%S`.
+    #[serde(default)]
+    pub timestamp_formats: Vec<String>,
+}
+
+/// Parser used for the files matching `glob` (relative to the source path).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserRule {
+    pub glob: String,
+    #[serde(flatten)]
+    pub parser: ParserConfig,
+}
+
+pub struct Source {
+    config: SourceConfig,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    parsers: ParserSelector,
+    multiline: Option<Multiline>,
+    timestamps: TimestampNormalizer,
+}
+
+impl Source {
+    pub fn new(config: SourceConfig) -> Result<Self> {
+        let compile = |globs: &[String]| -> Result<Vec<Pattern>> {
+            globs.iter().map(|glob| Ok(Pattern::new(glob)?)).collect()
+        };
+
+        let parsers = config.parsers.iter().try_fold(
+            ParserSelector::new(&config.path).with_default(&config.parser)?,
+            |selector, rule| selector.with_rule(&rule.glob, &rule.parser),
+        )?;
+
+        Ok(Source {
+            include: compile(&config.include)?,
+            exclude: compile(&config.exclude)?,
+            parsers,
+            multiline: config.multiline.as_ref().map(Multiline::new).transpose()?,
+            timestamps: TimestampNormalizer::new(
+                config.timezone.as_deref(),
+                &config.timestamp_formats,
+            )?,
+            config,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.config.path
+    }
+
+    /// Configuration of the polling watcher, when the source is polled.
+    pub fn poll_config(&self) -> Option<Config> {
+        self.config.poll_interval_ms.map(|interval| {
+            Config::default()
+                .with_poll_interval(Duration::from_millis(interval))
+                .with_compare_contents(self.config.poll_compare_contents)
+        })
+    }
+
+    pub(crate) fn parsers(&self) -> &ParserSelector {
+        &self.parsers
+    }
+
+    pub(crate) fn multiline(&self) -> Option<&Multiline> {
+        self.multiline.as_ref()
+    }
+
+    pub fn timestamps(&self) -> &TimestampNormalizer {
+        &self.timestamps
+    }
+
+    /// Tells whether the file under the source folder should be ingested.
+    pub fn matches(&self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.config.path) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+        let matches_any = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches_path_with(relative, MATCH_OPTIONS))
+        };
+
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+
+    /// Overrides the host and source of an entry read from the file according to the source.
+    pub fn apply(&self, path: &Path, log_entry: &mut DiskLogEntryDto) {
+        if self.config.host_from_dir {
+            if let Some(host) = self.host_dir(path) {
+                log_entry.host = host.into();
+            }
+        }
+
+        if log_entry.host.is_empty() {
+            if let Some(host) = &self.config.host {
+                log_entry.host = host.clone();
+            }
+        }
+
+        if let Some(source) = &self.config.source {
+            log_entry.source = source.clone();
+        }
+    }
+
+    /// Name of the first directory under the source folder containing the file.
+    fn host_dir<'a>(&self, path: &'a Path) -> Option<&'a str> {
+        let relative = path.strip_prefix(&self.config.path).ok()?;
+        let mut components = relative.components();
+
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(dir)), Some(_)) => dir.to_str(),
+            _ => None,
+        }
+    }
+}
@@ -1,45 +1,85 @@
 use anyhow::Result;
 use application::prelude::FileSystem;
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use notify::{Config, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{path::PathBuf, sync::Arc};
 use tracing::{error, info};
 
-pub fn async_watcher() -> notify::Result<(
-    RecommendedWatcher,
-    tokio::sync::mpsc::Receiver<Result<Event, notify::Error>>,
-)> {
-    let (tx, rx) = mpsc::channel(1);
+use super::pipeline::{event_pipeline, EventSender, PipelineStats, PipelineStatsSnapshot};
 
-    let watcher = RecommendedWatcher::new(
-        move |res| tx.blocking_send(res).expect("Failed to send event"),
-        Config::default(),
-    )?;
+/// Creates the watcher sending its events to the pipeline, the polling one when `poll`
+/// is set.
+pub fn async_watcher(
+    poll: Option<Config>,
+    sender: EventSender,
+) -> notify::Result<Box<dyn Watcher + Send + Sync>> {
+    let handler = move |res| sender.send_blocking(res);
 
-    Ok((watcher, rx))
+    let watcher: Box<dyn Watcher + Send + Sync> = match poll {
+        Some(config) => Box::new(PollWatcher::new(handler, config)?),
+        None => Box::new(RecommendedWatcher::new(handler, Config::default())?),
+    };
+
+    Ok(watcher)
+}
+
+/// Watcher of a folder, the folder is watched until it is dropped.
+pub struct WatchHandle {
+    _watcher: Box<dyn Watcher + Send + Sync>,
+    path: String,
+    stats: Arc<PipelineStats>,
+    fs: Arc<dyn FileSystem + Send + Sync>,
 }
 
-pub fn watch_dir<T>(path: &str, fs: Arc<T>) -> Result<RecommendedWatcher>
+impl WatchHandle {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn stats(&self) -> PipelineStatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    /// Number of lines of the folder which were stored with their invalid UTF-8 replaced.
+    pub fn invalid_utf8_lines(&self) -> u64 {
+        self.fs.invalid_utf8_lines()
+    }
+}
+
+/// Watches the folder and ingests the changed files.
+///
+/// The files which changed while the server was down are caught up first, the events
+/// received meanwhile wait in the pipeline and are handled afterwards.
+pub fn watch_dir<T>(path: &str, poll: Option<Config>, fs: Arc<T>) -> Result<WatchHandle>
 where
     T: FileSystem + Send + Sync + 'static,
 {
-    let (mut watcher, mut rx) = async_watcher()?;
+    let polled = poll.is_some();
+    let watched_fs = fs.clone();
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    let mut watcher = async_watcher(poll, sender)?;
     watcher.watch(path.as_ref(), RecursiveMode::Recursive)?;
 
-    info!("Watching for folder '{path}' started");
+    if polled {
+        info!("Polling of folder '{path}' started");
+    } else {
+        info!("Watching for folder '{path}' started");
+    }
+
+    let root = PathBuf::from(path);
 
     tokio::task::spawn(async move {
-        while let Some(res) = rx.recv().await {
-            match res {
-                Ok(event) => {
-                    if let Err(e) = fs.handle_event(event).await {
-                        error!("Handle event error: {:?}", e)
-                    }
-                }
-                Err(e) => error!("Watch error: {:?}", e),
-            }
+        if let Err(e) = fs.rescan(&root).await {
+            error!("Rescan of '{}' error: {:?}", root.display(), e)
         }
+
+        pipeline.run().await;
     });
 
-    Ok(watcher)
+    Ok(WatchHandle {
+        _watcher: watcher,
+        path: path.into(),
+        stats,
+        fs: watched_fs,
+    })
 }
@@ -0,0 +1,187 @@
+mod msgpack;
+mod tcp;
+
+use std::io::Read;
+
+use anyhow::{anyhow, bail, Result};
+use application::prelude::DiskLogEntryDto;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use flate2::read::MultiGzDecoder;
+use serde_json::Value as JsonValue;
+
+use rmpv::Value;
+
+use crate::parsers::{severity_from_level, Priority};
+
+pub use msgpack::decode as decode_msgpack;
+pub use rmpv::Value as MsgpackValue;
+pub use tcp::listen_fluent;
+
+/// Upper bound for a forward message, compressed or not, protects against bogus input.
+const MAX_MESSAGE_SIZE: usize = 32 * 1024 * 1024;
+
+/// Extension type of the `EventTime` timestamps, seconds and nanoseconds.
+const EVENT_TIME_EXT: i8 = 0;
+
+/// Record fields holding the message, the first one present is used.
+const MESSAGE_FIELDS: [&str; 4] = ["message", "log", "MESSAGE", "msg"];
+
+/// Record fields holding the level of the message.
+const LEVEL_FIELDS: [&str; 3] = ["level", "severity", "log.level"];
+
+/// Record fields holding the host of the message.
+const HOST_FIELDS: [&str; 2] = ["host", "hostname"];
+
+/// Entries of a Fluent Forward message, with the chunk id to acknowledge them by.
+#[derive(Debug)]
+pub struct ForwardMessage {
+    pub entries: Vec<DiskLogEntryDto>,
+    pub chunk: Option<String>,
+}
+
+/// Decodes a message in any of the Forward protocol modes: message, forward, packed
+/// forward and compressed packed forward.
+///
+/// The Fluent tag becomes the source of the entries. The message and the level are
+/// taken from the usual record fields, the remaining fields are kept in the attributes.
+pub fn decode_forward(message: Value) -> Result<ForwardMessage> {
+    let mut items = match message {
+        Value::Array(items) if items.len() >= 2 => items.into_iter(),
+        _ => bail!("Forward message isn't an array of at least two items"),
+    };
+
+    let tag = match items.next().as_ref().and_then(Value::as_str) {
+        Some(tag) => tag.to_owned(),
+        None => bail!("Forward message doesn't start with a tag"),
+    };
+
+    let (events, option) = match items.next().expect("Message has two items") {
+        Value::Array(events) => (events, items.next()),
+        Value::String(entries) => {
+            let option = items.next();
+            (unpack(entries.as_bytes(), option.as_ref())?, option)
+        }
+        Value::Binary(entries) => {
+            let option = items.next();
+            (unpack(&entries, option.as_ref())?, option)
+        }
+        time => {
+            let record = items
+                .next()
+                .ok_or_else(|| anyhow!("Message mode event has no record"))?;
+            (vec![Value::Array(vec![time, record])], items.next())
+        }
+    };
+
+    let entries = events
+        .into_iter()
+        .map(|event| match event {
+            Value::Array(event) if event.len() == 2 => {
+                let mut event = event.into_iter();
+                let time = event.next().expect("Event has two items");
+                let record = event.next().expect("Event has two items");
+                to_log_entry(&tag, time, record)
+            }
+            _ => bail!("Event isn't an array of a time and a record"),
+        })
+        .collect::<Result<_>>()?;
+
+    let chunk = option
+        .as_ref()
+        .and_then(|option| option["chunk"].as_str())
+        .map(Into::into);
+
+    Ok(ForwardMessage { entries, chunk })
+}
+
+/// Decodes the entries of a packed forward message, gzip compressed if the option says so.
+fn unpack(entries: &[u8], option: Option<&Value>) -> Result<Vec<Value>> {
+    let compressed = option.and_then(|option| option["compressed"].as_str());
+
+    match compressed {
+        None | Some("text") => msgpack::decode_all(entries),
+        Some("gzip") => {
+            let mut decompressed = Vec::new();
+            MultiGzDecoder::new(entries)
+                .take(MAX_MESSAGE_SIZE as u64 + 1)
+                .read_to_end(&mut decompressed)?;
+
+            if decompressed.len() > MAX_MESSAGE_SIZE {
+                bail!("Decompressed entries exceed the limit of {MAX_MESSAGE_SIZE} bytes");
+            }
+
+            msgpack::decode_all(&decompressed)
+        }
+        Some(compression) => bail!("Unsupported compression {compression:?}"),
+    }
+}
+
+fn to_log_entry(tag: &str, time: Value, record: Value) -> Result<DiskLogEntryDto> {
+    let timestamp = event_time(time)?;
+
+    let mut record = match msgpack::into_json(record) {
+        JsonValue::Object(record) => record,
+        _ => bail!("Record isn't a map"),
+    };
+
+    let message = match MESSAGE_FIELDS
+        .iter()
+        .find_map(|field| record.remove(*field))
+    {
+        Some(JsonValue::String(message)) => message,
+        Some(message) => message.to_string(),
+        None => JsonValue::Object(record.clone()).to_string(),
+    };
+
+    let severity = LEVEL_FIELDS
+        .iter()
+        .find_map(|field| record.get(*field)?.as_str())
+        .and_then(severity_from_level)
+        .unwrap_or(Priority::DEFAULT.severity_name());
+
+    let host = HOST_FIELDS
+        .iter()
+        .find_map(|field| record.get(*field)?.as_str())
+        .unwrap_or_default()
+        .to_owned();
+
+    Ok(DiskLogEntryDto {
+        timestamp: timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        host,
+        severity: severity.into(),
+        facility: Priority::DEFAULT.facility_name().into(),
+        syslog_tag: format!("{tag}:"),
+        source: tag.into(),
+        message,
+        attributes: record,
+        ..Default::default()
+    })
+}
+
+/// Time of an event, either an `EventTime` or seconds since the epoch.
+fn event_time(time: Value) -> Result<DateTime<Utc>> {
+    let timestamp = match time {
+        Value::Ext(EVENT_TIME_EXT, data) if data.len() == 8 => {
+            let seconds = u32::from_be_bytes(data[..4].try_into()?);
+            let nanos = u32::from_be_bytes(data[4..].try_into()?);
+            Utc.timestamp_opt(seconds.into(), nanos).single()
+        }
+        Value::Integer(seconds) => {
+            let seconds = seconds
+                .as_i64()
+                .ok_or_else(|| anyhow!("Event time is out of range"))?;
+            Utc.timestamp_opt(seconds, 0).single()
+        }
+        Value::F32(seconds) => float_time(seconds.into()),
+        Value::F64(seconds) => float_time(seconds),
+        Value::Nil => Some(Utc::now()),
+        time => bail!("Invalid event time {time:?}"),
+    };
+
+    timestamp.ok_or_else(|| anyhow!("Event time is out of range"))
+}
+
+fn float_time(seconds: f64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(seconds.trunc() as i64, (seconds.fract() * 1e9) as u32)
+        .single()
+}
@@ -0,0 +1,84 @@
+use std::io::ErrorKind;
+
+use anyhow::{bail, Result};
+use rmpv::{
+    decode::{read_value_with_max_depth, Error},
+    Value,
+};
+use serde_json::Value as JsonValue;
+
+/// Maximum nesting of arrays and maps, protects the stack against malicious input.
+const MAX_DEPTH: usize = 64;
+
+/// Decodes the first value of `buffer` and returns it with the number of bytes it took.
+///
+/// Returns `None` while the buffer holds only a part of the value, e.g. until the rest
+/// of it arrives on a stream.
+pub fn decode(buffer: &[u8]) -> Result<Option<(Value, usize)>> {
+    let mut reader = buffer;
+
+    match read_value_with_max_depth(&mut reader, MAX_DEPTH) {
+        Ok(value) => Ok(Some((value, buffer.len() - reader.len()))),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(Error::DepthLimitExceeded) => {
+            bail!("Values are nested deeper than {MAX_DEPTH} levels")
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Decodes all the values of `buffer`, e.g. the entries of a packed forward message.
+pub fn decode_all(mut buffer: &[u8]) -> Result<Vec<Value>> {
+    let mut values = Vec::new();
+
+    while !buffer.is_empty() {
+        match decode(buffer)? {
+            Some((value, len)) => {
+                values.push(value);
+                buffer = &buffer[len..];
+            }
+            None => bail!("Entries end with an incomplete value"),
+        }
+    }
+
+    Ok(values)
+}
+
+/// JSON form of a value, strings which aren't UTF-8 and binaries are decoded lossily.
+pub fn into_json(value: Value) -> JsonValue {
+    match value {
+        Value::Nil => JsonValue::Null,
+        Value::Boolean(value) => value.into(),
+        Value::Integer(value) => match value.as_u64() {
+            Some(value) => value.into(),
+            None => value.as_i64().map_or(JsonValue::Null, Into::into),
+        },
+        Value::F32(value) => f64::from(value).into(),
+        Value::F64(value) => value.into(),
+        Value::String(value) => lossy(value.into_bytes()).into(),
+        Value::Binary(value) | Value::Ext(_, value) => lossy(value).into(),
+        Value::Array(values) => values.into_iter().map(into_json).collect(),
+        Value::Map(entries) => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (into_key(key), into_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Map keys are strings in practice, anything else is kept in its JSON form.
+fn into_key(key: Value) -> String {
+    match key {
+        Value::String(key) => lossy(key.into_bytes()),
+        Value::Binary(key) => lossy(key),
+        key => into_json(key).to_string(),
+    }
+}
+
+fn lossy(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    }
+}
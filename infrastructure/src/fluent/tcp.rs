@@ -0,0 +1,152 @@
+use std::{io::ErrorKind, net::SocketAddr, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use application::prelude::LogRepository;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, error, info, instrument};
+
+use super::{decode_forward, msgpack, MAX_MESSAGE_SIZE};
+
+/// Size of the reads from a connection.
+const READ_SIZE: usize = 64 * 1024;
+
+/// Binds the TCP listener and spawns a task accepting Fluent Forward connections.
+///
+/// The entries of each message are stored in one transaction, a message asking for
+/// an acknowledgement is acknowledged only once the transaction is committed. When
+/// storing fails the connection is closed, so that the sender retries right away.
+pub async fn listen_fluent<L>(address: &str, log_repo: L) -> Result<()>
+where
+    L: LogRepository + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(address).await?;
+
+    info!("Listening for Fluent Forward messages on tcp://{address}");
+
+    let log_repo = Arc::new(log_repo);
+
+    tokio::task::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("Fluent Forward accept error: {:?}", e);
+                    continue;
+                }
+            };
+
+            let log_repo = log_repo.clone();
+
+            tokio::task::spawn(async move {
+                if let Err(e) = handle_connection(stream, peer, &*log_repo).await {
+                    error!("Fluent Forward connection with {peer} error: {:?}", e)
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Reads the messages of a connection, which is closed on invalid input so that the sender
+/// doesn't wait for the acknowledgement of a dropped message.
+#[instrument(skip(stream, log_repo))]
+async fn handle_connection<L: LogRepository>(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    log_repo: &L,
+) -> Result<()> {
+    let mut buffer = Vec::with_capacity(READ_SIZE);
+    // An incomplete message is decoded again once the buffer doubled or the sender paused,
+    // so that a large message isn't decoded over and over while its bytes arrive
+    let mut retry_len = 0;
+    let mut paused = false;
+
+    loop {
+        let mut start = 0;
+
+        while start < buffer.len() && (paused || buffer.len() - start >= retry_len) {
+            match msgpack::decode(&buffer[start..])? {
+                Some((message, len)) => {
+                    start += len;
+                    retry_len = 0;
+                    handle_message(&mut stream, peer, message, log_repo).await?;
+                }
+                None => {
+                    retry_len = 2 * (buffer.len() - start);
+                    break;
+                }
+            }
+        }
+
+        buffer.drain(..start);
+
+        if buffer.len() > MAX_MESSAGE_SIZE {
+            bail!("Message exceeds the limit of {MAX_MESSAGE_SIZE} bytes");
+        }
+
+        if stream.read_buf(&mut buffer).await? == 0 {
+            break;
+        }
+
+        paused = read_available(&stream, &mut buffer, retry_len)?;
+    }
+
+    if !buffer.is_empty() {
+        bail!("Connection closed in the middle of a message");
+    }
+
+    debug!("Fluent Forward connection closed");
+
+    Ok(())
+}
+
+/// Appends the bytes which already arrived until the buffer holds `len` bytes, tells whether
+/// the sender paused before, e.g. as it waits for an acknowledgement.
+fn read_available(stream: &TcpStream, buffer: &mut Vec<u8>, len: usize) -> Result<bool> {
+    while buffer.len() < len.min(MAX_MESSAGE_SIZE + 1) {
+        match stream.try_read_buf(buffer) {
+            Ok(0) => return Ok(true),
+            Ok(_) => continue,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(true),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(false)
+}
+
+async fn handle_message<L: LogRepository>(
+    stream: &mut TcpStream,
+    peer: SocketAddr,
+    message: rmpv::Value,
+    log_repo: &L,
+) -> Result<()> {
+    let mut message = decode_forward(message).context("Invalid Fluent Forward message")?;
+
+    for entry in &mut message.entries {
+        if entry.host.is_empty() {
+            entry.host = peer.ip().to_string();
+        }
+    }
+
+    log_repo.create_logs(message.entries).await?;
+
+    if let Some(chunk) = message.chunk {
+        stream.write_all(&ack(&chunk)).await?;
+    }
+
+    Ok(())
+}
+
+/// `{"ack": chunk}` response acknowledging the message with the chunk id.
+fn ack(chunk: &str) -> Vec<u8> {
+    let mut response = Vec::new();
+    rmp::encode::write_map_len(&mut response, 1).expect("Cannot write to Vec");
+    rmp::encode::write_str(&mut response, "ack").expect("Cannot write to Vec");
+    rmp::encode::write_str(&mut response, chunk).expect("Cannot write to Vec");
+    response
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+
+/// Magic bytes starting a chunk of a GELF message.
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+
+/// Magic bytes, message id, sequence number and sequence count.
+const CHUNK_HEADER_SIZE: usize = 12;
+
+/// Maximum number of chunks of one message, as defined by GELF.
+const MAX_CHUNKS: u8 = 128;
+
+/// Time to receive all the chunks of a message, as defined by GELF.
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum number of messages waiting for their remaining chunks.
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// Maximum size of the chunks of the messages waiting for their remaining chunks.
+const MAX_PENDING_BYTES: usize = 32 * 1024 * 1024;
+
+/// Reassembles the GELF messages split into chunks sent as separate UDP datagrams.
+///
+/// Messages are told apart by their sender and id, so that a sender can't complete the
+/// message of another one. The oldest incomplete messages are dropped once there are too
+/// many of them.
+#[derive(Default)]
+pub struct ChunkAssembler {
+    messages: HashMap<(SocketAddr, [u8; 8]), PendingMessage>,
+    /// Size of the chunks of all the pending messages.
+    bytes: usize,
+    /// Number given to the next message, tells the oldest ones apart.
+    next_sequence: u64,
+}
+
+struct PendingMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+    sequence: u64,
+    started: Instant,
+}
+
+impl ChunkAssembler {
+    /// Returns the whole message once the datagram completes it, unchunked datagrams
+    /// are whole messages on their own.
+    ///
+    /// The messages whose chunks didn't all arrive in time are dropped.
+    pub fn push(&mut self, peer: SocketAddr, datagram: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.expire(Instant::now());
+
+        if !datagram.starts_with(&CHUNK_MAGIC) {
+            return Ok(Some(datagram.to_vec()));
+        }
+
+        if datagram.len() < CHUNK_HEADER_SIZE {
+            bail!(
+                "Chunk of {} bytes is shorter than its header",
+                datagram.len()
+            );
+        }
+
+        let id: [u8; 8] = datagram[2..10].try_into()?;
+        let number = datagram[10];
+        let count = datagram[11];
+
+        if count == 0 || count > MAX_CHUNKS || number >= count {
+            bail!("Invalid chunk {number} of {count}");
+        }
+
+        let key = (peer, id);
+
+        if !self.messages.contains_key(&key) && self.messages.len() >= MAX_PENDING_MESSAGES {
+            self.evict_oldest();
+        }
+
+        let sequence = &mut self.next_sequence;
+        let message = self.messages.entry(key).or_insert_with(|| {
+            *sequence += 1;
+            PendingMessage {
+                chunks: vec![None; count as usize],
+                received: 0,
+                bytes: 0,
+                sequence: *sequence,
+                started: Instant::now(),
+            }
+        });
+
+        if message.chunks.len() != count as usize {
+            bail!(
+                "Chunk count {count} differs from {} of the previous chunks",
+                message.chunks.len()
+            );
+        }
+
+        let chunk = &mut message.chunks[number as usize];
+
+        if chunk.is_none() {
+            let payload = &datagram[CHUNK_HEADER_SIZE..];
+            *chunk = Some(payload.to_vec());
+            message.received += 1;
+            message.bytes += payload.len();
+            self.bytes += payload.len();
+        }
+
+        if message.received < message.chunks.len() {
+            while self.bytes > MAX_PENDING_BYTES {
+                self.evict_oldest();
+            }
+
+            return Ok(None);
+        }
+
+        let message = self
+            .messages
+            .remove(&key)
+            .expect("Message was just completed");
+        self.bytes -= message.bytes;
+
+        Ok(Some(
+            message.chunks.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Number of messages waiting for their remaining chunks.
+    pub fn pending(&self) -> usize {
+        self.messages.len()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let bytes = &mut self.bytes;
+
+        self.messages.retain(|_, message| {
+            let pending = now.duration_since(message.started) < CHUNK_TIMEOUT;
+            if !pending {
+                *bytes -= message.bytes;
+            }
+            pending
+        });
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .messages
+            .iter()
+            .min_by_key(|(_, message)| message.sequence)
+            .map(|(key, _)| *key);
+
+        if let Some(message) = oldest.and_then(|key| self.messages.remove(&key)) {
+            self.bytes -= message.bytes;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+mod chunks;
+mod tcp;
+mod udp;
+
+use std::{io::Read, net::SocketAddr};
+
+use anyhow::{bail, Context, Result};
+use application::prelude::{DiskLogEntryDto, LogRepository};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::parsers::Priority;
+
+pub use chunks::ChunkAssembler;
+pub use tcp::listen_gelf_tcp;
+pub use udp::listen_gelf_udp;
+
+/// Upper bound for a decompressed GELF message, protects against compression bombs.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Level of the messages without one, as defined by GELF.
+const DEFAULT_LEVEL: u8 = 1;
+
+#[derive(Deserialize, Debug)]
+struct GelfMessage {
+    host: String,
+    short_message: String,
+    full_message: Option<String>,
+    timestamp: Option<f64>,
+    level: Option<u8>,
+    facility: Option<String>,
+    #[serde(flatten)]
+    fields: Map<String, Value>,
+}
+
+/// Decodes a GELF message, compressed by zlib or gzip or uncompressed.
+///
+/// The `_` prefixed additional fields are kept in the attributes without the prefix,
+/// together with `full_message`. The source is taken from the `_tag` field set by the
+/// Docker GELF log driver, or from the legacy `facility` field.
+pub fn decode_gelf(payload: &[u8]) -> Result<DiskLogEntryDto> {
+    let payload = decompress(payload)?;
+    let message = serde_json::from_slice::<GelfMessage>(&payload)?;
+
+    let level = message.level.unwrap_or(DEFAULT_LEVEL);
+
+    if level > 7 {
+        bail!("Level {level} is out of range");
+    }
+
+    let priority = Priority {
+        severity: level,
+        ..Priority::DEFAULT
+    };
+
+    let timestamp = match message.timestamp {
+        Some(timestamp) => Utc
+            .timestamp_opt(timestamp.trunc() as i64, (timestamp.fract() * 1e9) as u32)
+            .single()
+            .with_context(|| format!("Invalid timestamp {timestamp}"))?,
+        None => Utc::now(),
+    };
+
+    let mut attributes: Map<String, Value> = message
+        .fields
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix('_')?.to_owned(), value)))
+        .collect();
+
+    let source = match attributes.get("tag") {
+        Some(Value::String(tag)) => tag.clone(),
+        _ => message.facility.unwrap_or_default(),
+    };
+
+    if let Some(full_message) = message.full_message {
+        attributes.insert("full_message".into(), full_message.into());
+    }
+
+    Ok(DiskLogEntryDto {
+        timestamp: timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        host: message.host,
+        severity: priority.severity_name().into(),
+        facility: priority.facility_name().into(),
+        syslog_tag: if source.is_empty() {
+            String::new()
+        } else {
+            format!("{source}:")
+        },
+        source,
+        message: message.short_message,
+        attributes,
+        ..Default::default()
+    })
+}
+
+fn decompress(payload: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    let limit = MAX_MESSAGE_SIZE as u64 + 1;
+
+    match payload {
+        [0x1f, 0x8b, ..] => MultiGzDecoder::new(payload)
+            .take(limit)
+            .read_to_end(&mut decompressed)?,
+        [0x78, ..] => ZlibDecoder::new(payload)
+            .take(limit)
+            .read_to_end(&mut decompressed)?,
+        _ => return Ok(payload.to_vec()),
+    };
+
+    if decompressed.len() > MAX_MESSAGE_SIZE {
+        bail!("Decompressed message exceeds the limit of {MAX_MESSAGE_SIZE} bytes");
+    }
+
+    Ok(decompressed)
+}
+
+/// Decodes a single GELF message and stores it in the repository.
+///
+/// The sender address is used as the host when the message has an empty one.
+async fn store_message<L: LogRepository>(
+    log_repo: &L,
+    payload: &[u8],
+    peer: SocketAddr,
+) -> Result<()> {
+    let mut log_entry = decode_gelf(payload)?;
+
+    debug!("Received GELF message: {log_entry:?}");
+
+    if log_entry.host.is_empty() {
+        log_entry.host = peer.ip().to_string();
+    }
+
+    log_repo.create_log(log_entry).await?;
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{bail, Result};
+use application::prelude::LogRepository;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, error, info, instrument};
+
+use super::{store_message, MAX_MESSAGE_SIZE};
+
+/// Binds the TCP listener and spawns a task accepting GELF connections.
+///
+/// Messages sent over TCP are uncompressed and each is terminated by a null byte.
+pub async fn listen_gelf_tcp<L>(address: &str, log_repo: L) -> Result<()>
+where
+    L: LogRepository + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(address).await?;
+
+    info!("Listening for GELF messages on tcp://{address}");
+
+    let log_repo = Arc::new(log_repo);
+
+    tokio::task::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("GELF accept error: {:?}", e);
+                    continue;
+                }
+            };
+
+            let log_repo = log_repo.clone();
+
+            tokio::task::spawn(async move {
+                if let Err(e) = handle_connection(stream, peer, &*log_repo).await {
+                    error!("GELF connection with {peer} error: {:?}", e)
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+#[instrument(skip(stream, log_repo))]
+async fn handle_connection<L: LogRepository>(
+    stream: TcpStream,
+    peer: SocketAddr,
+    log_repo: &L,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut frame = Vec::new();
+        let n = (&mut reader)
+            .take(MAX_MESSAGE_SIZE as u64 + 1)
+            .read_until(0, &mut frame)
+            .await?;
+
+        if n == 0 {
+            break;
+        }
+
+        if frame.last() == Some(&0) {
+            frame.pop();
+        } else if frame.len() > MAX_MESSAGE_SIZE {
+            bail!("Message exceeds the limit of {MAX_MESSAGE_SIZE} bytes");
+        }
+
+        if frame.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        if let Err(e) = store_message(log_repo, &frame, peer).await {
+            error!("Handle GELF message error: {:?}", e)
+        }
+    }
+
+    debug!("GELF connection closed");
+
+    Ok(())
+}
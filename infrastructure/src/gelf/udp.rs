@@ -0,0 +1,50 @@
+use anyhow::Result;
+use application::prelude::LogRepository;
+use tokio::net::UdpSocket;
+use tracing::{error, info};
+
+use super::{store_message, ChunkAssembler};
+
+/// Maximum size of an UDP datagram, larger GELF messages are sent in chunks.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Binds the UDP socket and spawns a task storing every received GELF message,
+/// reassembling the chunked ones.
+pub async fn listen_gelf_udp<L>(address: &str, log_repo: L) -> Result<()>
+where
+    L: LogRepository + Send + Sync + 'static,
+{
+    let socket = UdpSocket::bind(address).await?;
+
+    info!("Listening for GELF messages on udp://{address}");
+
+    tokio::task::spawn(async move {
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+        let mut assembler = ChunkAssembler::default();
+
+        loop {
+            let (n, peer) = match socket.recv_from(&mut buffer).await {
+                Ok(datagram) => datagram,
+                Err(e) => {
+                    error!("GELF receive error: {:?}", e);
+                    continue;
+                }
+            };
+
+            let message = match assembler.push(peer, &buffer[..n]) {
+                Ok(Some(message)) => message,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Invalid GELF chunk from {peer}: {:?}", e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = store_message(&log_repo, &message, peer).await {
+                error!("Handle GELF message from {peer} error: {:?}", e)
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -1,12 +1,37 @@
 mod cache;
+mod elastic;
 mod file_system;
+mod fluent;
+mod gelf;
+mod otlp;
+mod parsers;
 mod repository;
+mod syslog;
 mod telemetry;
+mod tls;
 
 pub mod prelude {
     pub use super::cache::SkyTableCache;
-    pub use super::file_system::{watch_dir, LinuxFS};
+    pub use super::elastic::{
+        bulk_response, cluster_info, decode_bulk, BulkItem, ElasticFieldMap, ELASTIC_PRODUCT_HEADER,
+    };
+    pub use super::file_system::{
+        event_pipeline, watch_dir, EventPipeline, EventSender, LinuxFS, MultilineConfig,
+        ParserRule, PipelineStats, PipelineStatsSnapshot, Source, SourceConfig, WatchHandle,
+    };
+    pub use super::fluent::{
+        decode_forward, decode_msgpack, listen_fluent, ForwardMessage, MsgpackValue,
+    };
+    pub use super::gelf::{decode_gelf, listen_gelf_tcp, listen_gelf_udp, ChunkAssembler};
+    pub use super::otlp::{decode_otlp_logs, OtlpEncoding};
+    pub use super::parsers::{
+        decode_log_batch, parse_line, JournalJsonParser, ParserConfig, ParserSelector, RegexParser,
+        Rfc3164Parser, Rfc5424Parser, RsyslogJsonParser, SyslogParser, TimestampNormalizer,
+    };
     pub use super::repository::blacklist_repostiory::PgBlkLstRepo;
     pub use super::repository::log_repository::PgLogRepo;
+    pub use super::repository::rejected_line_repository::PgRejectedLineRepo;
+    pub use super::syslog::{listen_tcp, listen_udp, read_frame};
     pub use super::telemetry::{get_subscriber, init_subscriber};
+    pub use super::tls::common_name;
 }
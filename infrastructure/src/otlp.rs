@@ -0,0 +1,188 @@
+use anyhow::Result;
+use application::prelude::DiskLogEntryDto;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use opentelemetry_proto::tonic::{
+    collector::logs::v1::{ExportLogsServiceRequest, ExportLogsServiceResponse},
+    common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+    logs::v1::LogRecord,
+};
+use prost::Message;
+use serde_json::{Map, Value};
+
+use crate::parsers::{severity_from_level, Priority};
+
+/// Facility of the entries, OpenTelemetry logs don't have any.
+const FACILITY: &str = "user";
+
+/// Encoding of an OTLP/HTTP request and its response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpEncoding {
+    Protobuf,
+    Json,
+}
+
+impl OtlpEncoding {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next()?.trim() {
+            "application/x-protobuf" => Some(OtlpEncoding::Protobuf),
+            "application/json" => Some(OtlpEncoding::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            OtlpEncoding::Protobuf => "application/x-protobuf",
+            OtlpEncoding::Json => "application/json",
+        }
+    }
+
+    /// Body of the response to an export which was fully accepted.
+    pub fn success_response(self) -> Vec<u8> {
+        let response = ExportLogsServiceResponse::default();
+
+        match self {
+            OtlpEncoding::Protobuf => response.encode_to_vec(),
+            OtlpEncoding::Json => {
+                serde_json::to_vec(&response).expect("Cannot serialize OTLP response")
+            }
+        }
+    }
+}
+
+/// Decodes the log records of an OTLP/HTTP logs export.
+///
+/// The `host.name`, `service.name` and `process.pid` resource attributes fill the host,
+/// the source and the process id of the entries, all the resource attributes are kept
+/// in `resource`. The severity is mapped from the severity number onto the syslog names,
+/// the severity text is only used when the number is missing.
+pub fn decode_otlp_logs(body: &[u8], encoding: OtlpEncoding) -> Result<Vec<DiskLogEntryDto>> {
+    let request = match encoding {
+        OtlpEncoding::Protobuf => ExportLogsServiceRequest::decode(body)?,
+        OtlpEncoding::Json => serde_json::from_slice(body)?,
+    };
+
+    let mut log_entries = Vec::new();
+
+    for resource_logs in request.resource_logs {
+        let resource = resource_logs
+            .resource
+            .map(|resource| attributes_to_json(resource.attributes))
+            .unwrap_or_default();
+
+        for scope_logs in resource_logs.scope_logs {
+            for record in scope_logs.log_records {
+                log_entries.push(to_log_entry(record, scope_logs.scope.as_ref(), &resource));
+            }
+        }
+    }
+
+    Ok(log_entries)
+}
+
+fn to_log_entry(
+    record: LogRecord,
+    scope: Option<&InstrumentationScope>,
+    resource: &Map<String, Value>,
+) -> DiskLogEntryDto {
+    let nanos = match record.time_unix_nano {
+        0 => record.observed_time_unix_nano,
+        nanos => nanos,
+    };
+    let timestamp = match nanos {
+        0 => Utc::now(),
+        nanos => Utc.timestamp_nanos(nanos as i64),
+    };
+
+    let service = resource_string(resource, "service.name");
+    let mut attributes = attributes_to_json(record.attributes);
+
+    if let Some(scope) = scope.filter(|scope| !scope.name.is_empty()) {
+        attributes.insert("otel.scope.name".into(), scope.name.clone().into());
+    }
+
+    DiskLogEntryDto {
+        timestamp: timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        host: resource_string(resource, "host.name").unwrap_or_default(),
+        severity: severity_name(record.severity_number, &record.severity_text),
+        facility: FACILITY.into(),
+        syslog_tag: service
+            .as_ref()
+            .map(|service| format!("{service}:"))
+            .unwrap_or_default(),
+        source: service.clone().unwrap_or_default(),
+        message: match record.body.map(any_value_to_json) {
+            Some(Value::String(message)) => message,
+            Some(Value::Null) | None => String::new(),
+            Some(body) => body.to_string(),
+        },
+        app_name: service,
+        proc_id: resource_string(resource, "process.pid"),
+        attributes,
+        trace_id: valid_id(&record.trace_id, 16),
+        span_id: valid_id(&record.span_id, 8),
+        resource: resource.clone(),
+        ..Default::default()
+    }
+}
+
+/// Maps the OpenTelemetry severity number, in ranges of four, onto the syslog severity.
+/// Without a number the severity text is mapped like the levels of the other inputs.
+fn severity_name(number: i32, text: &str) -> String {
+    let name = match number {
+        1..=8 => "debug",
+        9..=12 => "info",
+        13..=16 => "warning",
+        17..=20 => "err",
+        21..=24 => "crit",
+        _ => severity_from_level(text).unwrap_or(Priority::DEFAULT.severity_name()),
+    };
+
+    name.into()
+}
+
+fn resource_string(resource: &Map<String, Value>, key: &str) -> Option<String> {
+    match resource.get(key)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// Trace and span ids of an invalid length or with all zeroes mean there's no trace.
+fn valid_id(id: &[u8], len: usize) -> Option<String> {
+    if id.len() != len || id.iter().all(|&b| b == 0) {
+        return None;
+    }
+
+    Some(to_hex(id))
+}
+
+fn attributes_to_json(attributes: Vec<KeyValue>) -> Map<String, Value> {
+    attributes
+        .into_iter()
+        .map(|attribute| {
+            let value = attribute.value.map_or(Value::Null, any_value_to_json);
+            (attribute.key, value)
+        })
+        .collect()
+}
+
+fn any_value_to_json(value: AnyValue) -> Value {
+    match value.value {
+        Some(any_value::Value::StringValue(value)) => Value::String(value),
+        Some(any_value::Value::BoolValue(value)) => Value::Bool(value),
+        Some(any_value::Value::IntValue(value)) => value.into(),
+        Some(any_value::Value::DoubleValue(value)) => value.into(),
+        Some(any_value::Value::ArrayValue(array)) => {
+            Value::Array(array.values.into_iter().map(any_value_to_json).collect())
+        }
+        Some(any_value::Value::KvlistValue(list)) => Value::Object(attributes_to_json(list.values)),
+        Some(any_value::Value::BytesValue(bytes)) => Value::String(to_hex(&bytes)),
+        None => Value::Null,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
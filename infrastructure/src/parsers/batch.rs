@@ -0,0 +1,32 @@
+use anyhow::Result;
+use application::prelude::DiskLogEntryDto;
+
+use super::{normalize_log_entry, parse_line, RsyslogJsonParser, TimestampNormalizer};
+
+/// Decodes a batch of log entries, either a JSON array or newline delimited JSON objects,
+/// both shaped as `DiskLogEntryDto`.
+///
+/// Each record is decoded and validated on its own, so that one invalid record doesn't
+/// reject the whole batch. Only a body which isn't UTF-8 or a malformed array is an error.
+/// The timestamps without an offset are read in the timezone of `timestamps`.
+pub fn decode_log_batch(
+    body: &[u8],
+    timestamps: &TimestampNormalizer,
+) -> Result<Vec<Result<DiskLogEntryDto>>> {
+    let body = std::str::from_utf8(body)?;
+
+    if body.trim_start().starts_with('[') {
+        let records = serde_json::from_str::<Vec<serde_json::Value>>(body)?;
+
+        return Ok(records
+            .into_iter()
+            .map(|record| normalize_log_entry(serde_json::from_value(record)?, timestamps))
+            .collect());
+    }
+
+    Ok(body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_line(&RsyslogJsonParser, line, timestamps))
+        .collect())
+}
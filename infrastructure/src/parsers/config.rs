@@ -0,0 +1,52 @@
+use anyhow::Result;
+use application::prelude::LogParser;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Framing, JournalJsonParser, RegexParser, Rfc3164Parser, Rfc5424Parser, RsyslogJsonParser,
+    SyslogParser,
+};
+
+/// Configuration of a parser, selected by its `format`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum ParserConfig {
+    #[default]
+    RsyslogJson,
+    Rfc3164,
+    Rfc5424,
+    /// RFC 5424 or RFC 3164, detected per line.
+    Syslog,
+    Regex {
+        pattern: String,
+    },
+    Grok {
+        pattern: String,
+    },
+    /// systemd journal JSON, `journalctl -o json`.
+    JournalJson,
+    /// systemd journal export format, `journalctl -o export`.
+    JournalExport,
+}
+
+impl ParserConfig {
+    pub fn build(&self) -> Result<Box<dyn LogParser>> {
+        Ok(match self {
+            ParserConfig::RsyslogJson => Box::new(RsyslogJsonParser),
+            ParserConfig::Rfc3164 => Box::new(Rfc3164Parser),
+            ParserConfig::Rfc5424 => Box::new(Rfc5424Parser),
+            ParserConfig::Syslog => Box::new(SyslogParser),
+            ParserConfig::Regex { pattern } => Box::new(RegexParser::new(pattern)?),
+            ParserConfig::Grok { pattern } => Box::new(RegexParser::from_grok(pattern)?),
+            // Export entries reach the parser converted into journal JSON
+            ParserConfig::JournalJson | ParserConfig::JournalExport => Box::new(JournalJsonParser),
+        })
+    }
+
+    pub(crate) fn framing(&self) -> Framing {
+        match self {
+            ParserConfig::JournalExport => Framing::JournalExport,
+            _ => Framing::Lines,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+
+use super::journal::{export_entry_len, export_to_json, MAX_EXPORT_ENTRY};
+
+/// Upper bound for a line, longer lines are split into records of this size which are
+/// rejected, so that a file without newlines isn't held in memory as a whole.
+pub(crate) const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// How the contents of a file are split into the records handed to its parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Framing {
+    Lines,
+    /// Entries of the journal export format, ended by an empty line. Their binary fields
+    /// may hold newlines, they are converted into journal JSON before being parsed.
+    JournalExport,
+}
+
+impl Framing {
+    /// Length of the complete records at the start of `buffer`.
+    ///
+    /// A last record cut at the size limit isn't complete, so that the rest of the record
+    /// always follows it and is told apart from the next record.
+    pub(crate) fn complete_len(self, buffer: &[u8]) -> usize {
+        let mut len = 0;
+        let mut oversize_len = 0;
+
+        while let Some(record_len) = self.record_len(&buffer[len..]) {
+            oversize_len = match self.is_oversize(&buffer[len..len + record_len]) {
+                true => record_len,
+                false => 0,
+            };
+            len += record_len;
+        }
+
+        len - oversize_len
+    }
+
+    /// Splits `buffer` into records, the last one may be incomplete.
+    pub(crate) fn records(self, buffer: &[u8]) -> Records<'_> {
+        Records {
+            framing: self,
+            rest: buffer,
+        }
+    }
+
+    /// Tells whether the record was cut at the size limit, the rest of it is the next record.
+    pub(crate) fn is_oversize(self, record: &[u8]) -> bool {
+        match self {
+            Framing::Lines => record.len() == MAX_LINE_BYTES && record.last() != Some(&b'\n'),
+            Framing::JournalExport => record.len() > MAX_EXPORT_ENTRY,
+        }
+    }
+
+    /// Text of the record handed to the parser, invalid UTF-8 of lines is replaced by `U+FFFD`.
+    pub(crate) fn decode(self, record: &[u8]) -> Result<Cow<'_, str>> {
+        match self {
+            Framing::Lines if self.is_oversize(record) => {
+                bail!("Line exceeds the limit of {MAX_LINE_BYTES} bytes")
+            }
+            Framing::Lines => Ok(String::from_utf8_lossy(record)),
+            Framing::JournalExport if self.is_oversize(record) => {
+                bail!("Journal entry exceeds the limit of {MAX_EXPORT_ENTRY} bytes")
+            }
+            Framing::JournalExport => Ok(Cow::Owned(export_to_json(record)?)),
+        }
+    }
+
+    /// Length of the record at the start of `buffer`, `None` if it isn't complete.
+    fn record_len(self, buffer: &[u8]) -> Option<usize> {
+        if buffer.is_empty() {
+            return None;
+        }
+
+        match self {
+            Framing::Lines => {
+                let line = &buffer[..buffer.len().min(MAX_LINE_BYTES)];
+
+                match line.iter().position(|&b| b == b'\n') {
+                    Some(idx) => Some(idx + 1),
+                    None if line.len() == MAX_LINE_BYTES => Some(MAX_LINE_BYTES),
+                    None => None,
+                }
+            }
+            Framing::JournalExport => export_entry_len(buffer),
+        }
+    }
+}
+
+pub(crate) struct Records<'a> {
+    framing: Framing,
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let len = self
+            .framing
+            .record_len(self.rest)
+            .unwrap_or(self.rest.len());
+        let (record, rest) = self.rest.split_at(len);
+        self.rest = rest;
+
+        Some(record)
+    }
+}
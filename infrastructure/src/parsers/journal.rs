@@ -0,0 +1,248 @@
+use anyhow::{anyhow, bail, Context, Result};
+use application::prelude::{DiskLogEntryDto, LogParser};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde_json::{Map, Value};
+
+use super::Priority;
+
+/// Upper bound for a binary field of the export format, protects against a bogus length
+/// holding up the rest of the file.
+const MAX_BINARY_FIELD: u64 = 64 * 1024 * 1024;
+
+/// Upper bound for an entry of the export format, with room for a binary field at the limit.
+/// Longer entries are cut after one more byte and rejected.
+pub(crate) const MAX_EXPORT_ENTRY: usize = MAX_BINARY_FIELD as usize + 1024 * 1024;
+
+/// Fields holding the time of the entry in microseconds since the epoch, the first present wins.
+const TIMESTAMP_FIELDS: [&str; 2] = ["__REALTIME_TIMESTAMP", "_SOURCE_REALTIME_TIMESTAMP"];
+
+/// Parser of the systemd journal JSON format (`journalctl -o json`), one entry per line.
+///
+/// `_HOSTNAME`, `PRIORITY`, `SYSLOG_FACILITY`, `SYSLOG_IDENTIFIER`, `_SYSTEMD_UNIT`,
+/// `_PID` and `MESSAGE` are mapped onto the entry, the remaining fields are kept in the
+/// attributes. Values which aren't UTF-8 are arrays of bytes in this format, they are
+/// decoded lossily.
+pub struct JournalJsonParser;
+
+impl LogParser for JournalJsonParser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        let mut fields = match serde_json::from_str(line)? {
+            Value::Object(fields) => fields,
+            _ => bail!("Journal entry isn't an object"),
+        };
+
+        for value in fields.values_mut() {
+            *value = decode_value(value.take());
+        }
+
+        let microseconds = TIMESTAMP_FIELDS
+            .iter()
+            .find_map(|field| fields.remove(*field))
+            .ok_or_else(|| anyhow!("Journal entry has no timestamp"))?;
+        let microseconds = text(&microseconds)
+            .parse::<i64>()
+            .with_context(|| format!("Invalid timestamp {microseconds}"))?;
+        let timestamp = Utc
+            .timestamp_opt(
+                microseconds.div_euclid(1_000_000),
+                microseconds.rem_euclid(1_000_000) as u32 * 1000,
+            )
+            .single()
+            .ok_or_else(|| anyhow!("Timestamp {microseconds} is out of range"))?;
+
+        let mut take = |field: &str| fields.remove(field).map(|value| text(&value));
+
+        let severity = match take("PRIORITY") {
+            Some(severity) => severity.parse::<u8>().ok().filter(|&severity| severity < 8),
+            None => Some(Priority::DEFAULT.severity),
+        }
+        .ok_or_else(|| anyhow!("Invalid PRIORITY"))?;
+        let facility = match take("SYSLOG_FACILITY") {
+            Some(facility) => facility
+                .parse::<u8>()
+                .ok()
+                .filter(|&facility| facility < 24),
+            None => Some(Priority::DEFAULT.facility),
+        }
+        .ok_or_else(|| anyhow!("Invalid SYSLOG_FACILITY"))?;
+        let priority = Priority { facility, severity };
+
+        let identifier = take("SYSLOG_IDENTIFIER");
+        let pid = take("_PID");
+        let syslog_tag = match (&identifier, &pid) {
+            (Some(identifier), Some(pid)) => format!("{identifier}[{pid}]:"),
+            (Some(identifier), None) => format!("{identifier}:"),
+            (None, _) => String::new(),
+        };
+
+        Ok(DiskLogEntryDto {
+            timestamp: timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            host: take("_HOSTNAME").unwrap_or_default(),
+            severity: priority.severity_name().into(),
+            facility: priority.facility_name().into(),
+            syslog_tag,
+            source: take("_SYSTEMD_UNIT")
+                .or_else(|| identifier.clone())
+                .unwrap_or_default(),
+            message: take("MESSAGE").unwrap_or_default(),
+            app_name: identifier,
+            proc_id: pid,
+            attributes: fields,
+            ..Default::default()
+        })
+    }
+}
+
+/// Length of the export format entry at the start of `buffer`, including the empty line
+/// ending it, or `None` if the entry isn't complete yet.
+///
+/// An entry with a malformed binary field, e.g. one over the size limit, ends at the field,
+/// so that it's rejected rather than waited for. So does an entry over `MAX_EXPORT_ENTRY`,
+/// after `MAX_EXPORT_ENTRY + 1` bytes.
+pub(crate) fn export_entry_len(buffer: &[u8]) -> Option<usize> {
+    let mut position = 0;
+
+    let end = loop {
+        match scan_field(buffer, position) {
+            Scan::Field { end, .. } if end > MAX_EXPORT_ENTRY => break Some(end),
+            Scan::Field { end, .. } => position = end,
+            Scan::EntryEnd(end) | Scan::Malformed(end) => break Some(end),
+            Scan::Incomplete => break None,
+        }
+    };
+
+    match end {
+        Some(end) if end <= MAX_EXPORT_ENTRY => Some(end),
+        None if buffer.len() <= MAX_EXPORT_ENTRY => None,
+        _ => Some(MAX_EXPORT_ENTRY + 1),
+    }
+}
+
+/// Converts an entry of the journal export format (`journalctl -o export`) into the journal
+/// JSON format, the way `journalctl -o json` does: values which aren't UTF-8 become arrays
+/// of bytes and fields present more than once become arrays of their values.
+pub(crate) fn export_to_json(entry: &[u8]) -> Result<String> {
+    let mut fields = Map::new();
+    let mut position = 0;
+
+    loop {
+        let (name, value) = match scan_field(entry, position) {
+            Scan::Field { name, value, end } => {
+                position = end;
+                (&entry[name.0..name.1], &entry[value.0..value.1])
+            }
+            Scan::EntryEnd(_) => break,
+            Scan::Incomplete if entry[position..].is_empty() => break,
+            Scan::Incomplete => bail!("Journal entry ends in the middle of a field"),
+            Scan::Malformed(_) => bail!("Malformed binary field at {position}"),
+        };
+
+        let name = std::str::from_utf8(name)
+            .ok()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| anyhow!("Invalid field name {:?}", String::from_utf8_lossy(name)))?;
+        let value = match std::str::from_utf8(value) {
+            Ok(value) => Value::String(value.into()),
+            Err(_) => value.iter().map(|&b| Value::from(b)).collect(),
+        };
+
+        match fields.get_mut(name) {
+            Some(Value::Array(values)) if values.iter().all(|value| !value.is_number()) => {
+                values.push(value)
+            }
+            Some(previous) => *previous = Value::Array(vec![previous.take(), value]),
+            None => {
+                fields.insert(name.into(), value);
+            }
+        }
+    }
+
+    if fields.is_empty() {
+        return Ok(String::new());
+    }
+
+    Ok(Value::Object(fields).to_string())
+}
+
+/// Result of scanning for a field, positions are offsets into the scanned buffer.
+enum Scan {
+    Field {
+        name: (usize, usize),
+        value: (usize, usize),
+        end: usize,
+    },
+    EntryEnd(usize),
+    Malformed(usize),
+    Incomplete,
+}
+
+/// Scans the field at `position`, either `NAME=value\n` or, for binary values,
+/// `NAME\n` followed by the length as a little endian 64 bit integer, the value and `\n`.
+fn scan_field(buffer: &[u8], position: usize) -> Scan {
+    let line_len = match buffer[position..].iter().position(|&b| b == b'\n') {
+        Some(line_len) => line_len,
+        None => return Scan::Incomplete,
+    };
+    let line = &buffer[position..position + line_len];
+    let line_end = position + line_len + 1;
+
+    if line.is_empty() {
+        return Scan::EntryEnd(line_end);
+    }
+
+    if let Some(separator) = line.iter().position(|&b| b == b'=') {
+        return Scan::Field {
+            name: (position, position + separator),
+            value: (position + separator + 1, position + line_len),
+            end: line_end,
+        };
+    }
+
+    let length = match buffer.get(line_end..line_end + 8) {
+        Some(length) => u64::from_le_bytes(length.try_into().expect("Length has 8 bytes")),
+        None => return Scan::Incomplete,
+    };
+
+    if length > MAX_BINARY_FIELD {
+        return Scan::Malformed(line_end + 8);
+    }
+
+    let value_start = line_end + 8;
+    let value_end = value_start + length as usize;
+
+    match buffer.get(value_end) {
+        Some(b'\n') => Scan::Field {
+            name: (position, position + line_len),
+            value: (value_start, value_end),
+            end: value_end + 1,
+        },
+        // The value isn't followed by a newline, the entry can't be trusted any further
+        Some(_) => Scan::Malformed(value_end),
+        None => Scan::Incomplete,
+    }
+}
+
+/// Decodes the arrays of bytes used for the values which aren't UTF-8.
+fn decode_value(value: Value) -> Value {
+    match value {
+        Value::Array(values) if values.iter().all(Value::is_u64) => {
+            let bytes: Vec<u8> = values
+                .iter()
+                .filter_map(|b| b.as_u64()?.try_into().ok())
+                .collect();
+            Value::String(String::from_utf8_lossy(&bytes).into())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(decode_value).collect()),
+        value => value,
+    }
+}
+
+/// Text of a field, the first value of the fields present more than once.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values.first().map(text).unwrap_or_default(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
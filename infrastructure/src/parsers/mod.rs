@@ -0,0 +1,60 @@
+mod batch;
+mod config;
+mod framing;
+mod journal;
+mod priority;
+mod regex;
+mod rfc3164;
+mod rfc5424;
+mod rsyslog_json;
+mod selector;
+mod timestamp;
+
+use anyhow::Result;
+use application::prelude::{DiskLogEntryDto, LogParser};
+
+pub use self::regex::RegexParser;
+pub use batch::decode_log_batch;
+pub use config::ParserConfig;
+pub(crate) use framing::{Framing, MAX_LINE_BYTES};
+pub use journal::JournalJsonParser;
+pub use priority::{severity_from_level, Priority};
+pub use rfc3164::Rfc3164Parser;
+pub use rfc5424::{is_rfc5424, Rfc5424Parser};
+pub use rsyslog_json::RsyslogJsonParser;
+pub use selector::ParserSelector;
+pub(crate) use selector::MATCH_OPTIONS;
+pub(crate) use timestamp::parse_zoned;
+pub use timestamp::TimestampNormalizer;
+
+/// Parser of syslog messages in either of the formats, telling them apart by the RFC 5424 version field.
+pub struct SyslogParser;
+
+impl LogParser for SyslogParser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        if is_rfc5424(line) {
+            Rfc5424Parser.parse(line)
+        } else {
+            Rfc3164Parser.parse(line)
+        }
+    }
+}
+
+/// Parses the line and normalizes the timestamp of the entry into RFC 3339, so that
+/// the line isn't rejected only once it's being stored.
+pub fn parse_line(
+    parser: &dyn LogParser,
+    line: &str,
+    timestamps: &TimestampNormalizer,
+) -> Result<DiskLogEntryDto> {
+    normalize_log_entry(parser.parse(line)?, timestamps)
+}
+
+fn normalize_log_entry(
+    mut log_entry: DiskLogEntryDto,
+    timestamps: &TimestampNormalizer,
+) -> Result<DiskLogEntryDto> {
+    log_entry.timestamp = timestamps.normalize(&log_entry.timestamp)?;
+
+    Ok(log_entry)
+}
@@ -0,0 +1,104 @@
+use anyhow::{bail, Result};
+
+const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+/// Decoded syslog PRI part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Priority {
+    pub facility: u8,
+    pub severity: u8,
+}
+
+impl Priority {
+    /// Priority assumed when a message has no PRI part (`user.notice`, RFC 3164 4.3.3).
+    pub const DEFAULT: Priority = Priority {
+        facility: 1,
+        severity: 5,
+    };
+
+    pub fn from_value(value: u8) -> Result<Self> {
+        if value > 191 {
+            bail!("Priority value {value} is out of range");
+        }
+
+        Ok(Priority {
+            facility: value >> 3,
+            severity: value & 7,
+        })
+    }
+
+    /// Splits a leading `<PRI>` from the message.
+    ///
+    /// Returns `None` if the message doesn't start with a PRI part.
+    pub fn split(message: &str) -> Option<(Result<Priority>, &str)> {
+        let rest = message.strip_prefix('<')?;
+        let end = rest.find('>')?;
+        let digits = &rest[..end];
+
+        if digits.is_empty() || digits.len() > 3 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let priority = digits
+            .parse::<u8>()
+            .map_err(anyhow::Error::from)
+            .and_then(Priority::from_value);
+
+        Some((priority, &rest[end + 1..]))
+    }
+
+    pub fn facility_name(&self) -> &'static str {
+        FACILITIES[self.facility as usize]
+    }
+
+    pub fn severity_name(&self) -> &'static str {
+        SEVERITIES[self.severity as usize]
+    }
+}
+
+/// Maps a level name used by logging libraries, e.g. `WARN` or `fatal`, onto the name
+/// of the syslog severity.
+pub fn severity_from_level(level: &str) -> Option<&'static str> {
+    let severity = match level.to_lowercase().as_str() {
+        "emerg" | "emergency" | "panic" => 0,
+        "alert" => 1,
+        "crit" | "critical" | "fatal" => 2,
+        "err" | "error" => 3,
+        "warn" | "warning" => 4,
+        "notice" => 5,
+        "info" | "information" | "informational" => 6,
+        "debug" | "trace" => 7,
+        _ => return None,
+    };
+
+    Some(SEVERITIES[severity])
+}
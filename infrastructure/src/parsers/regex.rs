@@ -0,0 +1,142 @@
+use anyhow::{anyhow, bail, Result};
+use application::prelude::{DiskLogEntryDto, LogParser};
+use chrono::Local;
+use regex::{Captures, Regex};
+use serde_json::Value;
+
+use super::timestamp::parse_zoned;
+
+/// Patterns available in grok templates as `%{NAME}` or `%{NAME:field}`.
+const GROK_PATTERNS: [(&str, &str); 20] = [
+    ("WORD", r"\b\w+\b"),
+    ("NOTSPACE", r"\S+"),
+    ("SPACE", r"\s*"),
+    ("DATA", r".*?"),
+    ("GREEDYDATA", r".*"),
+    ("INT", r"[+-]?\d+"),
+    ("POSINT", r"\b[1-9]\d*\b"),
+    ("NUMBER", r"[+-]?(?:\d+(?:\.\d+)?|\.\d+)"),
+    ("IPV4", r"(?:\d{1,3}\.){3}\d{1,3}"),
+    ("IPV6", r"[0-9A-Fa-f]*:[0-9A-Fa-f:.]+"),
+    ("IP", r"(?:\d{1,3}\.){3}\d{1,3}|[0-9A-Fa-f]*:[0-9A-Fa-f:.]+"),
+    ("HOSTNAME", r"\b[0-9A-Za-z][0-9A-Za-z\-_.]*\b"),
+    (
+        "IPORHOST",
+        r"(?:\d{1,3}\.){3}\d{1,3}|[0-9A-Fa-f]*:[0-9A-Fa-f:.]+|\b[0-9A-Za-z][0-9A-Za-z\-_.]*\b",
+    ),
+    ("PROG", r"[\w._/%-]+"),
+    ("QS", r#""(?:[^"\\]|\\.)*""#),
+    (
+        "SYSLOGTIMESTAMP",
+        r"[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}",
+    ),
+    (
+        "TIMESTAMP_ISO8601",
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2})?",
+    ),
+    (
+        "HTTPDATE",
+        r"\d{2}/[A-Za-z]{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}",
+    ),
+    (
+        "LOGLEVEL",
+        r"(?i:trace|debug|notice|info|warn(?:ing)?|err(?:or)?|crit(?:ical)?|alert|fatal|severe|emerg(?:ency)?)",
+    ),
+    ("URIPATHPARAM", r"\S+"),
+];
+
+/// Parser extracting log entry fields from named capture groups of a regular expression.
+///
+/// Groups named after `DiskLogEntryDto` fields (`timestamp`, `host`, `severity`, `facility`,
+/// `syslog_tag`, `source`, `message`, `app_name`, `proc_id`, `msg_id`) fill these fields, every
+/// other named group is stored as an attribute. Without a `message` group the whole line is
+/// the message and without a `timestamp` group the current time is used.
+pub struct RegexParser {
+    regex: Regex,
+}
+
+impl RegexParser {
+    pub fn new(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+        })
+    }
+
+    /// Creates the parser from a grok template, e.g. `%{IPORHOST:host} %{GREEDYDATA:message}`.
+    pub fn from_grok(template: &str) -> Result<Self> {
+        let reference = Regex::new(r"%\{(\w+)(?::(\w+))?\}")?;
+        let mut error = None;
+
+        let pattern = reference.replace_all(template, |captures: &Captures| {
+            let name = &captures[1];
+
+            match GROK_PATTERNS.iter().find(|(pattern, _)| *pattern == name) {
+                Some((_, regex)) => match captures.get(2) {
+                    Some(field) => format!("(?P<{}>{regex})", field.as_str()),
+                    None => format!("(?:{regex})"),
+                },
+                None => {
+                    error = Some(anyhow!("Unknown grok pattern '{name}'"));
+                    String::new()
+                }
+            }
+        });
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        Self::new(&format!("^{pattern}$"))
+    }
+}
+
+impl LogParser for RegexParser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        let captures = match self.regex.captures(line) {
+            Some(captures) => captures,
+            None => bail!("Line doesn't match the pattern '{}'", self.regex),
+        };
+
+        let mut log_entry = DiskLogEntryDto {
+            message: line.into(),
+            ..Default::default()
+        };
+        let mut timestamp = None;
+
+        for name in self.regex.capture_names().flatten() {
+            let value = match captures.name(name) {
+                Some(value) => value.as_str(),
+                None => continue,
+            };
+
+            match name {
+                "timestamp" => timestamp = Some(value),
+                "host" => log_entry.host = value.into(),
+                "severity" => log_entry.severity = value.into(),
+                "facility" => log_entry.facility = value.into(),
+                "syslog_tag" => log_entry.syslog_tag = value.into(),
+                "source" => log_entry.source = value.into(),
+                "message" => log_entry.message = value.into(),
+                "app_name" => log_entry.app_name = Some(value.into()),
+                "proc_id" => log_entry.proc_id = Some(value.into()),
+                "msg_id" => log_entry.msg_id = Some(value.into()),
+                attribute => {
+                    log_entry
+                        .attributes
+                        .insert(attribute.into(), Value::String(value.into()));
+                }
+            }
+        }
+
+        log_entry.timestamp = match timestamp {
+            // Timestamps without an offset are left to the normalizer of the source
+            Some(timestamp) => match parse_zoned(timestamp) {
+                Some(timestamp) => timestamp.to_rfc3339(),
+                None => timestamp.into(),
+            },
+            None => Local::now().to_rfc3339(),
+        };
+
+        Ok(log_entry)
+    }
+}
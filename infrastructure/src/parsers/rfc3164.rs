@@ -0,0 +1,105 @@
+use anyhow::Result;
+use application::prelude::{DiskLogEntryDto, LogParser};
+use chrono::{DateTime, Local};
+
+use super::{timestamp::is_bsd_timestamp, Priority};
+
+/// Parser of BSD syslog messages (RFC 3164).
+///
+/// The parser is as lenient as syslog relays are: a missing PRI part falls back
+/// to `user.notice`, a missing timestamp is replaced with the current time and a
+/// missing hostname is left empty, so the caller can fill it in with the sender address.
+///
+/// BSD timestamps are kept as they are, they're read in the timezone of the source
+/// once normalized.
+pub struct Rfc3164Parser;
+
+impl LogParser for Rfc3164Parser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        parse_rfc3164(line)
+    }
+}
+
+fn parse_rfc3164(message: &str) -> Result<DiskLogEntryDto> {
+    let message = message.trim_end_matches(['\r', '\n', '\0']);
+
+    let (priority, rest) = match Priority::split(message) {
+        Some((priority, rest)) => (priority?, rest),
+        None => (Priority::DEFAULT, message),
+    };
+
+    let (timestamp, host, content) = match split_timestamp(rest) {
+        Some((timestamp, rest)) => {
+            let (host, content) = split_host(rest.trim_start());
+            (timestamp, host, content)
+        }
+        None => (Local::now().to_rfc3339(), "", rest.trim_start()),
+    };
+
+    let (program, tag, content) = split_tag(content);
+
+    Ok(DiskLogEntryDto {
+        timestamp,
+        host: host.into(),
+        severity: priority.severity_name().into(),
+        facility: priority.facility_name().into(),
+        syslog_tag: tag.into(),
+        source: program.into(),
+        message: content.into(),
+        ..Default::default()
+    })
+}
+
+/// Splits either a `Mmm dd hh:mm:ss` timestamp or a RFC 3339 one (sent by
+/// rsyslog with the high precision template) from the beginning of the message.
+fn split_timestamp(message: &str) -> Option<(String, &str)> {
+    let token_end = message.find(' ').unwrap_or(message.len());
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(&message[..token_end]) {
+        return Some((timestamp.to_rfc3339(), &message[token_end..]));
+    }
+
+    let timestamp = message
+        .get(..15)
+        .filter(|timestamp| is_bsd_timestamp(timestamp))?;
+
+    Some((timestamp.into(), &message[15..]))
+}
+
+fn split_host(message: &str) -> (&str, &str) {
+    let token_end = message.find(' ').unwrap_or(message.len());
+    let token = &message[..token_end];
+
+    // Senders frequently omit the hostname, in which case the first token is already the tag.
+    if token.ends_with(':') || token.contains('[') {
+        ("", message)
+    } else {
+        (token, message[token_end..].trim_start())
+    }
+}
+
+/// Splits the message content into program name, full tag (e.g. `sshd[42]:`) and message.
+fn split_tag(content: &str) -> (&str, &str, &str) {
+    let program_end = match content.find(|c: char| c == ':' || c == '[' || c.is_whitespace()) {
+        Some(0) | None => return ("", "", content),
+        Some(end) => end,
+    };
+
+    let mut tag_end = program_end;
+
+    if content[tag_end..].starts_with('[') {
+        match content[tag_end..].find(']') {
+            Some(idx) => tag_end += idx + 1,
+            None => return ("", "", content),
+        }
+    }
+
+    if content[tag_end..].starts_with(':') {
+        tag_end += 1;
+    }
+
+    let message = &content[tag_end..];
+    let message = message.strip_prefix(' ').unwrap_or(message);
+
+    (&content[..program_end], &content[..tag_end], message)
+}
@@ -0,0 +1,95 @@
+use anyhow::{anyhow, bail, Result};
+use application::prelude::{DiskLogEntryDto, LogParser};
+use chrono::{DateTime, Local};
+use domain::prelude::StructuredData;
+
+use super::Priority;
+
+const NIL_VALUE: &str = "-";
+
+/// Tells whether the message is in the RFC 5424 format, i.e. has `1` as the version after PRI.
+pub fn is_rfc5424(message: &str) -> bool {
+    match Priority::split(message) {
+        Some((_, rest)) => rest.starts_with("1 "),
+        None => false,
+    }
+}
+
+/// Parser of RFC 5424 syslog messages.
+///
+/// APP-NAME, PROCID and MSGID are stored as separate fields and every STRUCTURED-DATA
+/// element is kept in the attributes, keyed by its SD-ID.
+pub struct Rfc5424Parser;
+
+impl LogParser for Rfc5424Parser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        parse_rfc5424(line)
+    }
+}
+
+fn parse_rfc5424(message: &str) -> Result<DiskLogEntryDto> {
+    let message = message.trim_end_matches(['\r', '\n', '\0']);
+
+    let (priority, rest) = Priority::split(message)
+        .ok_or_else(|| anyhow!("RFC 5424 message has to start with PRI"))?;
+    let priority = priority?;
+
+    let rest = rest
+        .strip_prefix("1 ")
+        .ok_or_else(|| anyhow!("Unsupported syslog protocol version"))?;
+
+    let mut header = rest.splitn(6, ' ');
+    let mut next_field = |name| {
+        header
+            .next()
+            .ok_or_else(|| anyhow!("RFC 5424 message is missing the {name} field"))
+    };
+
+    let timestamp = match next_field("TIMESTAMP")? {
+        NIL_VALUE => Local::now().into(),
+        timestamp => DateTime::parse_from_rfc3339(timestamp)?,
+    };
+    let host = nil_to_option(next_field("HOSTNAME")?);
+    let app_name = nil_to_option(next_field("APP-NAME")?);
+    let proc_id = nil_to_option(next_field("PROCID")?);
+    let msg_id = nil_to_option(next_field("MSGID")?);
+    let rest = next_field("STRUCTURED-DATA")?;
+
+    let (structured_data, rest) = StructuredData::parse(rest)?;
+
+    let content = match rest {
+        "" => "",
+        rest => match rest.strip_prefix(' ') {
+            Some(content) => content.strip_prefix('\u{feff}').unwrap_or(content),
+            None => bail!("Expected space after STRUCTURED-DATA"),
+        },
+    };
+
+    let syslog_tag = match (app_name, proc_id) {
+        (Some(app_name), Some(proc_id)) => format!("{app_name}[{proc_id}]:"),
+        (Some(app_name), None) => format!("{app_name}:"),
+        _ => String::new(),
+    };
+
+    Ok(DiskLogEntryDto {
+        timestamp: timestamp.to_rfc3339(),
+        host: host.unwrap_or_default().into(),
+        severity: priority.severity_name().into(),
+        facility: priority.facility_name().into(),
+        syslog_tag,
+        source: app_name.unwrap_or_default().into(),
+        message: content.into(),
+        app_name: app_name.map(Into::into),
+        proc_id: proc_id.map(Into::into),
+        msg_id: msg_id.map(Into::into),
+        attributes: structured_data.0,
+        ..Default::default()
+    })
+}
+
+fn nil_to_option(field: &str) -> Option<&str> {
+    match field {
+        NIL_VALUE => None,
+        field => Some(field),
+    }
+}
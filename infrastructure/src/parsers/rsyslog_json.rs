@@ -0,0 +1,13 @@
+use anyhow::Result;
+use application::prelude::{DiskLogEntryDto, LogParser};
+
+/// Parser of the JSON lines written by rsyslog's `DiskLogEntryDto` shaped template.
+///
+/// The timestamp is kept as it is, `parse_line` normalizes it.
+pub struct RsyslogJsonParser;
+
+impl LogParser for RsyslogJsonParser {
+    fn parse(&self, line: &str) -> Result<DiskLogEntryDto> {
+        Ok(serde_json::from_str::<DiskLogEntryDto>(line)?)
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use application::prelude::LogParser;
+use glob::{MatchOptions, Pattern};
+
+use super::{Framing, ParserConfig, RsyslogJsonParser};
+
+pub(crate) const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Picks the parser for a file based on glob rules matched against its path
+/// relative to the watched directory. The first matching rule wins, files
+/// matching no rule are parsed by the default parser, rsyslog JSON unless set.
+pub struct ParserSelector {
+    root: PathBuf,
+    rules: Vec<Rule>,
+    default: Rule,
+}
+
+struct Rule {
+    pattern: Pattern,
+    config: ParserConfig,
+    parser: Box<dyn LogParser>,
+}
+
+impl ParserSelector {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            rules: Vec::new(),
+            default: Rule {
+                pattern: Pattern::new("**").expect("Invalid default pattern"),
+                config: ParserConfig::RsyslogJson,
+                parser: Box::new(RsyslogJsonParser),
+            },
+        }
+    }
+
+    pub fn with_default(mut self, parser: &ParserConfig) -> Result<Self> {
+        self.default.config = parser.clone();
+        self.default.parser = parser.build()?;
+        Ok(self)
+    }
+
+    pub fn with_rule(mut self, glob: &str, parser: &ParserConfig) -> Result<Self> {
+        self.rules.push(Rule {
+            pattern: Pattern::new(glob)?,
+            config: parser.clone(),
+            parser: parser.build()?,
+        });
+        Ok(self)
+    }
+
+    pub fn select(&self, path: &Path) -> &dyn LogParser {
+        self.find_rule(path).parser.as_ref()
+    }
+
+    /// Configuration of the parser selected for the file, kept with its rejected lines.
+    pub fn config(&self, path: &Path) -> &ParserConfig {
+        &self.find_rule(path).config
+    }
+
+    /// How the file is split into the records handed to its parser.
+    pub(crate) fn framing(&self, path: &Path) -> Framing {
+        self.find_rule(path).config.framing()
+    }
+
+    fn find_rule(&self, path: &Path) -> &Rule {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+
+        self.rules
+            .iter()
+            .find(|rule| rule.pattern.matches_path_with(relative, MATCH_OPTIONS))
+            .unwrap_or(&self.default)
+    }
+}
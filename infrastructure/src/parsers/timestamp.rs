@@ -0,0 +1,179 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Formats of the timestamps without an offset, e.g. `2022-11-12 16:06:05.250`.
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// Turns the timestamps found in log files into RFC 3339.
+///
+/// Accepts RFC 3339, the HTTP/CLF format (`10/Oct/2000:13:55:36 -0700`), the custom
+/// strftime formats, epoch seconds, milliseconds, microseconds or nanoseconds (told apart
+/// by their number of digits, seconds from 9 digits so that e.g. a year isn't one), ISO 8601 without an offset and BSD syslog (`Mmm dd hh:mm:ss`).
+/// The timestamps without an offset are read in the timezone of the normalizer, the local
+/// one by default.
+#[derive(Debug, Clone, Default)]
+pub struct TimestampNormalizer {
+    timezone: Timezone,
+    formats: Vec<String>,
+}
+
+impl TimestampNormalizer {
+    /// `timezone` is either an IANA name (`Europe/Berlin`), a fixed offset (`+02:00`)
+    /// or `local`.
+    pub fn new(timezone: Option<&str>, formats: &[String]) -> Result<Self> {
+        Ok(TimestampNormalizer {
+            timezone: timezone
+                .map(Timezone::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            formats: formats.to_vec(),
+        })
+    }
+
+    /// Normalizes the timestamp into RFC 3339.
+    pub fn normalize(&self, timestamp: &str) -> Result<String> {
+        self.parse(timestamp)
+            .map(|timestamp| timestamp.to_rfc3339())
+            .ok_or_else(|| anyhow!("Invalid timestamp {timestamp:?}"))
+    }
+
+    pub fn parse(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        let timestamp = timestamp.trim();
+
+        parse_zoned(timestamp)
+            .or_else(|| self.parse_custom(timestamp))
+            .or_else(|| parse_epoch(timestamp))
+            .or_else(|| {
+                NAIVE_FORMATS.iter().find_map(|format| {
+                    let naive = NaiveDateTime::parse_from_str(timestamp, format).ok()?;
+                    self.timezone.localize(&naive)
+                })
+            })
+            .or_else(|| self.parse_bsd(timestamp))
+    }
+
+    fn parse_custom(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        self.formats.iter().find_map(|format| {
+            DateTime::parse_from_str(timestamp, format)
+                .ok()
+                .or_else(|| {
+                    let naive = NaiveDateTime::parse_from_str(timestamp, format).ok()?;
+                    self.timezone.localize(&naive)
+                })
+        })
+    }
+
+    /// Parses a BSD syslog timestamp (`Mmm dd hh:mm:ss`).
+    ///
+    /// The timestamp has no year. A timestamp that would land more than a day in the
+    /// future belongs to the previous year (e.g. a message received right after New Year).
+    fn parse_bsd(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        let normalized = timestamp.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let now = self.timezone.now();
+        let parse = |year: i32| {
+            NaiveDateTime::parse_from_str(&format!("{year} {normalized}"), "%Y %b %d %H:%M:%S").ok()
+        };
+
+        let timestamp = match parse(now.year()) {
+            Some(timestamp) if timestamp <= now + Duration::days(1) => timestamp,
+            _ => parse(now.year() - 1)?,
+        };
+
+        self.timezone.localize(&timestamp)
+    }
+}
+
+/// Parses the timestamps carrying their offset: RFC 3339 and HTTP/CLF.
+pub(crate) fn parse_zoned(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .or_else(|_| DateTime::parse_from_str(timestamp, "%d/%b/%Y:%H:%M:%S %z"))
+        .ok()
+}
+
+/// Tells whether the text is a BSD syslog timestamp (`Mmm dd hh:mm:ss`), whatever its year.
+pub(crate) fn is_bsd_timestamp(timestamp: &str) -> bool {
+    let normalized = timestamp.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // A leap year, so that February 29 is accepted
+    NaiveDateTime::parse_from_str(&format!("2000 {normalized}"), "%Y %b %d %H:%M:%S").is_ok()
+}
+
+/// Parses an epoch timestamp, its unit told apart by the number of digits: seconds from
+/// 9 digits (March 1973) up to 11 (the year 5138), then milliseconds, microseconds and
+/// nanoseconds. Shorter numbers, e.g. a year or an id, aren't timestamps.
+fn parse_epoch(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    let (integer, fraction) = timestamp.split_once('.').unwrap_or((timestamp, ""));
+    let is_digits = |text: &str| text.bytes().all(|b| b.is_ascii_digit());
+
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let unit_digits = match integer.len() {
+        9..=11 => 9,
+        12..=14 => 6,
+        15..=17 => 3,
+        18..=19 => 0,
+        _ => return None,
+    };
+
+    // Nanoseconds of the fraction of the unit, its digits beyond nanoseconds are dropped
+    let fraction = format!("{:0<width$.width$}", fraction, width = unit_digits);
+    let nanoseconds = integer.parse::<i128>().ok()? * 10i128.pow(unit_digits as u32)
+        + fraction.parse::<i128>().unwrap_or(0);
+
+    let timestamp = Utc.timestamp_nanos(nanoseconds.try_into().ok()?);
+
+    Some(timestamp.into())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+enum Timezone {
+    #[default]
+    Local,
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    fn parse(name: &str) -> Result<Self> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Timezone::Local);
+        }
+
+        if let Ok(timezone) = name.parse::<Tz>() {
+            return Ok(Timezone::Named(timezone));
+        }
+
+        match name.parse::<FixedOffset>() {
+            Ok(offset) => Ok(Timezone::Fixed(offset)),
+            Err(_) => bail!("Unknown timezone {name:?}"),
+        }
+    }
+
+    /// Time of the timestamp in the timezone, the earlier one when it's ambiguous,
+    /// e.g. when the clocks go back.
+    fn localize(&self, naive: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Timezone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|timestamp| timestamp.fixed_offset()),
+            Timezone::Named(timezone) => timezone
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|timestamp| timestamp.fixed_offset()),
+            Timezone::Fixed(offset) => offset.from_local_datetime(naive).earliest(),
+        }
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        match self {
+            Timezone::Local => Local::now().naive_local(),
+            Timezone::Named(timezone) => Utc::now().with_timezone(timezone).naive_local(),
+            Timezone::Fixed(offset) => Utc::now().with_timezone(offset).naive_local(),
+        }
+    }
+}
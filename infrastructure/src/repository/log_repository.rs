@@ -1,12 +1,24 @@
-use application::prelude::{DiskLogEntryDto, LogRepository};
+use application::prelude::{DiskLogEntryDto, LogRepository, RejectedLineDto};
 use async_trait::async_trait;
-use domain::prelude::{LogEntry, LogEntryFilter, LogEntryFilterQueryBuilder, ReposiotryResult};
-use sqlx::{types::chrono, PgPool};
+use domain::prelude::{
+    FileOffset, LogEntry, LogEntryFilter, LogEntryFilterQueryBuilder, ReposiotryResult,
+    RepositoryError,
+};
+use sqlx::{types::chrono, Executor, PgPool, Postgres, QueryBuilder, Transaction};
 use tracing::instrument;
 use uuid::Uuid;
 
+use super::rejected_line_repository::insert_rejected_lines;
+use crate::parsers::parse_zoned;
+
+/// Number of columns bound for every inserted log entry.
+const LOG_COLUMNS: usize = 17;
+
+/// Postgres accepts at most 65535 bind parameters in one statement.
+const MAX_ROWS_PER_INSERT: usize = u16::MAX as usize / LOG_COLUMNS;
+
 pub struct PgLogRepo {
-    pool: PgPool,
+    pub(super) pool: PgPool,
 }
 
 impl PgLogRepo {
@@ -40,7 +52,7 @@ impl LogRepository for PgLogRepo {
         skip(self)
     )]
     async fn get_logs_by_filter(&self, filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
-        let mut query_builder = LogEntryFilterQueryBuilder::new(filter);
+        let mut query_builder = LogEntryFilterQueryBuilder::new(filter)?;
         let query = query_builder.to_sql_query();
 
         let logs = query.fetch_all(&self.pool).await?;
@@ -50,13 +62,17 @@ impl LogRepository for PgLogRepo {
 
     #[instrument(name = "Creating log entry in the database", skip(self))]
     async fn create_log(&self, dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
-        let date = chrono::DateTime::parse_from_rfc3339(&dto.timestamp)?;
+        let date = event_time(&dto)?;
         let id = Uuid::new_v4();
 
         sqlx::query!(
             r#"
-            INSERT INTO logs (id, timestamp, host, severity, facility, syslog_tag, source, message)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO logs (
+                id, timestamp, host, severity, facility, syslog_tag, source, message,
+                client_identity, app_name, proc_id, msg_id, attributes, trace_id, span_id,
+                resource, redactions
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             "#,
             id,
             date,
@@ -66,6 +82,15 @@ impl LogRepository for PgLogRepo {
             dto.syslog_tag,
             dto.source,
             dto.message,
+            dto.client_identity,
+            dto.app_name,
+            dto.proc_id,
+            dto.msg_id,
+            serde_json::Value::Object(dto.attributes),
+            dto.trace_id,
+            dto.span_id,
+            serde_json::Value::Object(dto.resource),
+            &dto.redactions,
         )
         .execute(&self.pool)
         .await?;
@@ -73,6 +98,39 @@ impl LogRepository for PgLogRepo {
         Ok(id)
     }
 
+    #[instrument(
+        name = "Creating log entries in the database",
+        skip_all,
+        fields(count = dtos.len())
+    )]
+    async fn create_logs(&self, dtos: Vec<DiskLogEntryDto>) -> ReposiotryResult<Vec<Uuid>> {
+        let mut transaction = self.pool.begin().await?;
+        let ids = insert_logs(&mut transaction, dtos).await?;
+        transaction.commit().await?;
+
+        Ok(ids)
+    }
+
+    #[instrument(
+        name = "Creating log entries with their file offset in the database",
+        skip(self, dtos, rejected_dtos),
+        fields(count = dtos.len(), rejected = rejected_dtos.len())
+    )]
+    async fn create_logs_with_offset(
+        &self,
+        dtos: Vec<DiskLogEntryDto>,
+        rejected_dtos: Vec<RejectedLineDto>,
+        offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        let mut transaction = self.pool.begin().await?;
+        let ids = insert_logs(&mut transaction, dtos).await?;
+        insert_rejected_lines(&mut transaction, rejected_dtos).await?;
+        upsert_offset(&mut transaction, &offset).await?;
+        transaction.commit().await?;
+
+        Ok(ids)
+    }
+
     #[instrument(name = "Deleting log entry from the database", skip(self))]
     async fn delete_log(&self, id: uuid::Uuid) -> ReposiotryResult<()> {
         sqlx::query!("DELETE FROM logs WHERE id = $1", id)
@@ -82,3 +140,84 @@ impl LogRepository for PgLogRepo {
         Ok(())
     }
 }
+
+pub(super) async fn insert_logs(
+    transaction: &mut Transaction<'_, Postgres>,
+    dtos: Vec<DiskLogEntryDto>,
+) -> ReposiotryResult<Vec<Uuid>> {
+    let rows = dtos
+        .into_iter()
+        .map(|dto| {
+            let date = event_time(&dto)?;
+            Ok((Uuid::new_v4(), date, dto))
+        })
+        .collect::<ReposiotryResult<Vec<_>>>()?;
+
+    let ids = rows.iter().map(|(id, _, _)| *id).collect();
+    let mut rows = rows.into_iter().peekable();
+
+    while rows.peek().is_some() {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO logs (id, timestamp, host, severity, facility, syslog_tag, source, \
+             message, client_identity, app_name, proc_id, msg_id, attributes, trace_id, \
+             span_id, resource, redactions) ",
+        );
+
+        query_builder.push_values(
+            rows.by_ref().take(MAX_ROWS_PER_INSERT),
+            |mut row, (id, date, dto)| {
+                row.push_bind(id)
+                    .push_bind(date)
+                    .push_bind(dto.host)
+                    .push_bind(dto.severity)
+                    .push_bind(dto.facility)
+                    .push_bind(dto.syslog_tag)
+                    .push_bind(dto.source)
+                    .push_bind(dto.message)
+                    .push_bind(dto.client_identity)
+                    .push_bind(dto.app_name)
+                    .push_bind(dto.proc_id)
+                    .push_bind(dto.msg_id)
+                    .push_bind(serde_json::Value::Object(dto.attributes))
+                    .push_bind(dto.trace_id)
+                    .push_bind(dto.span_id)
+                    .push_bind(serde_json::Value::Object(dto.resource))
+                    .push_bind(dto.redactions);
+            },
+        );
+
+        query_builder.build().execute(&mut *transaction).await?;
+    }
+
+    Ok(ids)
+}
+
+/// Time of the entry. The inputs normalize the timestamps in their own timezone, so one
+/// without an offset is rejected rather than read in the local timezone.
+fn event_time(dto: &DiskLogEntryDto) -> ReposiotryResult<chrono::DateTime<chrono::FixedOffset>> {
+    parse_zoned(&dto.timestamp)
+        .ok_or_else(|| RepositoryError::Parsing(format!("Invalid timestamp {:?}", dto.timestamp)))
+}
+
+pub(super) async fn upsert_offset<'e, E>(executor: E, offset: &FileOffset) -> ReposiotryResult<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO file_offsets (path, dev, ino, byte_offset, complete)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (path) DO UPDATE
+        SET dev = $2, ino = $3, byte_offset = $4, complete = $5, updated_at = now()
+        "#,
+        offset.path,
+        offset.dev,
+        offset.ino,
+        offset.byte_offset,
+        offset.complete,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
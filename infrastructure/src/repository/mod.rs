@@ -1,2 +1,4 @@
 pub mod blacklist_repostiory;
 pub mod log_repository;
+pub mod offset_repository;
+pub mod rejected_line_repository;
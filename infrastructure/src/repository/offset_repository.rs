@@ -0,0 +1,38 @@
+use application::prelude::OffsetRepository;
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, ReposiotryResult};
+use tracing::instrument;
+
+use super::log_repository::{upsert_offset, PgLogRepo};
+
+// Offsets are kept by the log repository as they are written in the same
+// transaction as the log entries read up to them.
+#[async_trait]
+impl OffsetRepository for PgLogRepo {
+    #[instrument(name = "Retrieving file offset from the database", skip(self))]
+    async fn get_offset(&self, path: &str) -> ReposiotryResult<Option<FileOffset>> {
+        let offset = sqlx::query_as!(
+            FileOffset,
+            "SELECT path, dev, ino, byte_offset, complete FROM file_offsets WHERE path = $1",
+            path
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(offset)
+    }
+
+    #[instrument(name = "Storing file offset in the database", skip(self))]
+    async fn set_offset(&self, offset: FileOffset) -> ReposiotryResult<()> {
+        upsert_offset(&self.pool, &offset).await
+    }
+
+    #[instrument(name = "Deleting file offset from the database", skip(self))]
+    async fn delete_offset(&self, path: &str) -> ReposiotryResult<()> {
+        sqlx::query!("DELETE FROM file_offsets WHERE path = $1", path)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use application::prelude::{DiskLogEntryDto, RejectedLineDto, RejectedLineRepository};
+use async_trait::async_trait;
+use domain::prelude::{RejectedLine, ReposiotryResult};
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::log_repository::insert_logs;
+
+/// Number of columns bound for every inserted rejected line.
+const REJECTED_LINE_COLUMNS: usize = 6;
+
+/// Postgres accepts at most 65535 bind parameters in one statement.
+const MAX_ROWS_PER_INSERT: usize = u16::MAX as usize / REJECTED_LINE_COLUMNS;
+
+pub struct PgRejectedLineRepo {
+    pool: PgPool,
+}
+
+impl PgRejectedLineRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RejectedLineRepository for PgRejectedLineRepo {
+    #[instrument(name = "Retrieving one rejected line from the database", skip(self))]
+    async fn get_rejected_line_by_id(&self, id: Uuid) -> ReposiotryResult<RejectedLine> {
+        let line = sqlx::query_as!(
+            RejectedLine,
+            "SELECT * FROM rejected_lines WHERE id = $1",
+            id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(line)
+    }
+
+    #[instrument(name = "Retrieving rejected lines from the database", skip(self))]
+    async fn get_rejected_lines(
+        &self,
+        path: Option<String>,
+        limit: i64,
+    ) -> ReposiotryResult<Vec<RejectedLine>> {
+        let lines = sqlx::query_as!(
+            RejectedLine,
+            r#"
+            SELECT * FROM rejected_lines
+            WHERE $1::TEXT IS NULL OR path = $1
+            ORDER BY rejected_at, path, byte_offset
+            LIMIT $2
+            "#,
+            path,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(name = "Resolving rejected line in the database", skip(self))]
+    async fn resolve_rejected_line(
+        &self,
+        id: Uuid,
+        dto: DiskLogEntryDto,
+    ) -> ReposiotryResult<Uuid> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM rejected_lines WHERE id = $1 RETURNING id", id)
+            .fetch_one(&mut transaction)
+            .await?;
+        let ids = insert_logs(&mut transaction, vec![dto]).await?;
+
+        transaction.commit().await?;
+
+        Ok(ids[0])
+    }
+
+    #[instrument(name = "Updating rejected line error in the database", skip(self))]
+    async fn update_rejected_line_error(&self, id: Uuid, error: &str) -> ReposiotryResult<()> {
+        sqlx::query!(
+            "UPDATE rejected_lines SET error = $2 WHERE id = $1",
+            id,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Deleting rejected line from the database", skip(self))]
+    async fn delete_rejected_line(&self, id: Uuid) -> ReposiotryResult<()> {
+        sqlx::query!("DELETE FROM rejected_lines WHERE id = $1", id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Purging rejected lines from the database", skip(self))]
+    async fn purge_rejected_lines(&self, path: Option<String>) -> ReposiotryResult<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM rejected_lines WHERE $1::TEXT IS NULL OR path = $1",
+            path
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+pub(super) async fn insert_rejected_lines(
+    transaction: &mut Transaction<'_, Postgres>,
+    dtos: Vec<RejectedLineDto>,
+) -> ReposiotryResult<()> {
+    let mut rows = dtos.into_iter().peekable();
+
+    while rows.peek().is_some() {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO rejected_lines (id, path, byte_offset, raw, error, parser) ",
+        );
+
+        query_builder.push_values(rows.by_ref().take(MAX_ROWS_PER_INSERT), |mut row, dto| {
+            row.push_bind(Uuid::new_v4())
+                .push_bind(dto.path)
+                .push_bind(dto.byte_offset)
+                .push_bind(dto.raw)
+                .push_bind(dto.error)
+                .push_bind(dto.parser);
+        });
+
+        query_builder.build().execute(&mut *transaction).await?;
+    }
+
+    Ok(())
+}
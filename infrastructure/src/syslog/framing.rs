@@ -0,0 +1,62 @@
+use anyhow::{bail, Result};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+/// Upper bound for a frame, protects against bogus length prefixes and endless lines.
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// Reads the next syslog frame from a stream.
+///
+/// Both framing methods used over TCP are supported and detected per frame:
+/// octet counting (`MSG-LEN SP SYSLOG-MSG`, RFC 5425/6587) when the frame
+/// starts with a digit, and non-transparent framing terminated by a newline otherwise.
+///
+/// Frames larger than `MAX_FRAME_SIZE` are rejected.
+///
+/// Returns `None` once the peer closes the connection.
+pub async fn read_frame<R>(reader: &mut R) -> Result<Option<Vec<u8>>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let first = match reader.fill_buf().await?.first() {
+        Some(byte) => *byte,
+        None => return Ok(None),
+    };
+
+    let mut frame = Vec::new();
+    let limit = MAX_FRAME_SIZE as u64 + 1;
+
+    if first.is_ascii_digit() {
+        (&mut *reader)
+            .take(limit)
+            .read_until(b' ', &mut frame)
+            .await?;
+
+        if frame.last() != Some(&b' ') {
+            bail!("Frame length prefix isn't followed by a space");
+        }
+
+        let length = std::str::from_utf8(&frame)?.trim_end().parse::<usize>()?;
+
+        if length > MAX_FRAME_SIZE {
+            bail!("Frame of {length} bytes exceeds the limit of {MAX_FRAME_SIZE} bytes");
+        }
+
+        frame.resize(length, 0);
+        reader.read_exact(&mut frame).await?;
+    } else {
+        (&mut *reader)
+            .take(limit)
+            .read_until(b'\n', &mut frame)
+            .await?;
+
+        if frame.last() != Some(&b'\n') && frame.len() > MAX_FRAME_SIZE {
+            bail!("Frame exceeds the limit of {MAX_FRAME_SIZE} bytes");
+        }
+
+        while matches!(frame.last(), Some(b'\n' | b'\r')) {
+            frame.pop();
+        }
+    }
+
+    Ok(Some(frame))
+}
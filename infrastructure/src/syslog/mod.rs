@@ -0,0 +1,42 @@
+mod framing;
+mod tcp;
+mod udp;
+
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use application::prelude::LogRepository;
+use tracing::debug;
+
+use crate::parsers::{parse_line, SyslogParser, TimestampNormalizer};
+
+pub use framing::read_frame;
+pub use tcp::listen_tcp;
+pub use udp::listen_udp;
+
+/// Parses a single syslog message and stores it in the repository.
+///
+/// The sender address is used as the host when the message doesn't carry one.
+async fn store_message<L: LogRepository>(
+    log_repo: &L,
+    timestamps: &TimestampNormalizer,
+    message: &[u8],
+    peer: SocketAddr,
+    client_identity: Option<&str>,
+) -> Result<()> {
+    let message = String::from_utf8_lossy(message);
+
+    debug!("Received syslog message: {message:?}");
+
+    let mut log_entry = parse_line(&SyslogParser, &message, timestamps)?;
+
+    if log_entry.host.is_empty() {
+        log_entry.host = peer.ip().to_string();
+    }
+
+    log_entry.client_identity = client_identity.map(Into::into);
+
+    log_repo.create_log(log_entry).await?;
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::{net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use application::prelude::LogRepository;
+use openssl::ssl::{Ssl, SslAcceptor};
+use tokio::{
+    io::{AsyncRead, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tokio_openssl::SslStream;
+use tracing::{debug, error, info, instrument};
+
+use super::{framing::read_frame, store_message};
+use crate::{parsers::TimestampNormalizer, tls::common_name};
+
+/// Time for a client to finish the TLS handshake, so that idle connections don't hold a task.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Binds the TCP listener and spawns a task accepting syslog connections.
+///
+/// When `tls` is set every connection has to complete a TLS handshake first (RFC 5425)
+/// with a client certificate carrying a common name, which is stored with each received
+/// log entry. The timestamps without an offset are read in the timezone of `timestamps`.
+pub async fn listen_tcp<L>(
+    address: &str,
+    log_repo: L,
+    tls: Option<SslAcceptor>,
+    timestamps: TimestampNormalizer,
+) -> Result<()>
+where
+    L: LogRepository + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(address).await?;
+    let scheme = if tls.is_some() { "tls" } else { "tcp" };
+
+    info!("Listening for syslog messages on {scheme}://{address}");
+
+    let log_repo = Arc::new(log_repo);
+    let tls = tls.map(Arc::new);
+    let timestamps = Arc::new(timestamps);
+
+    tokio::task::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("Syslog accept error: {:?}", e);
+                    continue;
+                }
+            };
+
+            let log_repo = log_repo.clone();
+            let tls = tls.clone();
+            let timestamps = timestamps.clone();
+
+            tokio::task::spawn(async move {
+                let result = match tls {
+                    Some(acceptor) => {
+                        handle_tls_connection(stream, peer, &acceptor, &*log_repo, &timestamps)
+                            .await
+                    }
+                    None => handle_connection(stream, peer, None, &*log_repo, &timestamps).await,
+                };
+
+                if let Err(e) = result {
+                    error!("Syslog connection with {peer} error: {:?}", e)
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_tls_connection<L: LogRepository>(
+    stream: TcpStream,
+    peer: SocketAddr,
+    acceptor: &SslAcceptor,
+    log_repo: &L,
+    timestamps: &TimestampNormalizer,
+) -> Result<()> {
+    let ssl = Ssl::new(acceptor.context())?;
+    let mut stream = SslStream::new(ssl, stream)?;
+    tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, Pin::new(&mut stream).accept())
+        .await
+        .map_err(|_| anyhow!("TLS handshake didn't finish in {TLS_HANDSHAKE_TIMEOUT:?}"))??;
+
+    let client_identity = stream
+        .ssl()
+        .peer_certificate()
+        .and_then(|cert| common_name(&cert))
+        .ok_or_else(|| anyhow!("Client certificate with a common name is required"))?;
+
+    debug!("TLS handshake with {peer} finished, client certificate: {client_identity}");
+
+    handle_connection(stream, peer, Some(client_identity), log_repo, timestamps).await
+}
+
+#[instrument(skip(stream, log_repo, timestamps))]
+async fn handle_connection<S, L>(
+    stream: S,
+    peer: SocketAddr,
+    client_identity: Option<String>,
+    log_repo: &L,
+    timestamps: &TimestampNormalizer,
+) -> Result<()>
+where
+    S: AsyncRead + Unpin,
+    L: LogRepository,
+{
+    let mut reader = BufReader::new(stream);
+
+    while let Some(frame) = read_frame(&mut reader).await? {
+        if frame.is_empty() {
+            continue;
+        }
+
+        let client_identity = client_identity.as_deref();
+
+        if let Err(e) = store_message(log_repo, timestamps, &frame, peer, client_identity).await {
+            error!("Handle syslog message error: {:?}", e)
+        }
+    }
+
+    debug!("Syslog connection closed");
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use application::prelude::LogRepository;
+use tokio::net::UdpSocket;
+use tracing::{error, info};
+
+use super::store_message;
+use crate::parsers::TimestampNormalizer;
+
+/// Maximum size of an UDP datagram, larger syslog messages are truncated by the sender anyway.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Binds the UDP socket and spawns a task storing every received syslog datagram.
+///
+/// The timestamps without an offset, e.g. the BSD syslog ones, are read in the timezone
+/// of `timestamps`.
+pub async fn listen_udp<L>(
+    address: &str,
+    log_repo: L,
+    timestamps: TimestampNormalizer,
+) -> Result<()>
+where
+    L: LogRepository + Send + Sync + 'static,
+{
+    let socket = UdpSocket::bind(address).await?;
+
+    info!("Listening for syslog messages on udp://{address}");
+
+    tokio::task::spawn(async move {
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+
+        loop {
+            match socket.recv_from(&mut buffer).await {
+                Ok((n, peer)) => {
+                    let datagram = &buffer[..n];
+
+                    if let Err(e) =
+                        store_message(&log_repo, &timestamps, datagram, peer, None).await
+                    {
+                        error!("Handle syslog datagram from {peer} error: {:?}", e)
+                    }
+                }
+                Err(e) => error!("Syslog receive error: {:?}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
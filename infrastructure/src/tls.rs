@@ -0,0 +1,9 @@
+use openssl::{nid::Nid, x509::X509};
+
+/// Common name of a client certificate, used as the identity of the client.
+pub fn common_name(cert: &X509) -> Option<String> {
+    cert.subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .next()
+        .and_then(|entry| entry.data().to_string().ok())
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use infrastructure::prelude::{bulk_response, decode_bulk, ElasticFieldMap};
+use serde_json::json;
+use uuid::Uuid;
+
+#[test]
+fn decode_filebeat_documents() {
+    let body = [
+        json!({ "create": { "_index": "filebeat-8.11.0" } }),
+        json!({
+            "@timestamp": "2022-11-12T15:06:05.250Z",
+            "message": "GET / 200",
+            "log": { "level": "warn", "file": { "path": "/var/log/app.log" } },
+            "host": { "name": "web-1", "os": { "family": "debian" } },
+            "service.name": "checkout",
+        }),
+        json!({ "index": {} }),
+        json!({ "@timestamp": 1668265565250_u64, "status": 200 }),
+    ];
+
+    let items = decode_bulk(
+        ndjson(&body).as_bytes(),
+        Some("vector"),
+        &Default::default(),
+    )
+    .expect("Cannot decode bulk request");
+
+    assert_eq!(items.len(), 2);
+
+    let log = items[0].log_entry.as_ref().unwrap();
+    assert_eq!(items[0].index, "filebeat-8.11.0");
+    assert_eq!(log.timestamp, "2022-11-12T15:06:05.250Z");
+    assert_eq!(log.host, "web-1");
+    assert_eq!(log.severity, "warning");
+    assert_eq!(log.source, "checkout");
+    assert_eq!(log.message, "GET / 200");
+    assert_eq!(
+        log.attributes["log"],
+        json!({ "file": { "path": "/var/log/app.log" } })
+    );
+    assert_eq!(
+        log.attributes["host"],
+        json!({ "os": { "family": "debian" } })
+    );
+    assert!(!log.attributes.contains_key("service.name"));
+
+    let log = items[1].log_entry.as_ref().unwrap();
+    assert_eq!(items[1].index, "vector");
+    assert_eq!(log.timestamp, "2022-11-12T15:06:05.250Z");
+    assert_eq!(log.source, "vector");
+    assert_eq!(log.severity, "notice");
+    assert_eq!(log.message, r#"{"status":200}"#);
+}
+
+#[test]
+fn failed_actions_are_reported_by_item() {
+    let body = [
+        json!({ "index": { "_index": "app" } }),
+        json!({ "message": "stored" }),
+        json!({ "delete": { "_index": "app", "_id": "1" } }),
+        json!({ "index": { "_index": "app" } }),
+        json!({ "@timestamp": "yesterday", "message": "invalid" }),
+        json!({ "update": { "_index": "app", "_id": "1" } }),
+        json!({ "doc": { "message": "changed" } }),
+        json!({ "create": { "_index": "app" } }),
+        json!({ "message": "dropped" }),
+    ];
+
+    let items = decode_bulk(ndjson(&body).as_bytes(), None, &Default::default())
+        .expect("Cannot decode bulk request");
+    let id = Uuid::new_v4();
+    let response = bulk_response(&items, &[id, Uuid::nil()], Duration::from_millis(3));
+
+    assert_eq!(response["errors"], true);
+    assert_eq!(response["took"], 3);
+    assert_eq!(response["items"][0]["index"]["_id"], id.to_string());
+    assert_eq!(response["items"][0]["index"]["status"], 201);
+    assert_eq!(response["items"][1]["delete"]["status"], 400);
+    assert_eq!(
+        response["items"][2]["index"]["error"]["type"],
+        "document_parsing_exception"
+    );
+    assert_eq!(response["items"][3]["update"]["status"], 400);
+    assert_eq!(response["items"][4]["create"]["result"], "noop");
+    assert_eq!(response["items"][4]["create"]["status"], 200);
+    assert_eq!(
+        response["items"][4]["create"]["_id"],
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn map_configured_fields_and_reject_malformed_requests() {
+    let fields = ElasticFieldMap {
+        message: vec!["msg".into()],
+        host: vec!["kubernetes.node.name".into()],
+        ..Default::default()
+    };
+    let body = [
+        json!({ "create": { "_index": "k8s" } }),
+        json!({ "msg": "ready", "kubernetes.node.name": "node-1", "message": "kept" }),
+    ];
+
+    let items = decode_bulk(ndjson(&body).as_bytes(), None, &fields).unwrap();
+    let log = items[0].log_entry.as_ref().unwrap();
+
+    assert_eq!(log.message, "ready");
+    assert_eq!(log.host, "node-1");
+    assert_eq!(log.attributes["message"], "kept");
+
+    let fields = ElasticFieldMap::default();
+    assert!(decode_bulk(b"{\"create\":{}}\n", Some("app"), &fields).is_err());
+    assert!(decode_bulk(b"{\"upsert\":{}}\n{}\n", Some("app"), &fields).is_err());
+    assert!(decode_bulk(b"not json\n", Some("app"), &fields).is_err());
+    assert!(
+        decode_bulk(b"{\"index\":{}}\n{}\n", None, &fields).unwrap()[0]
+            .log_entry
+            .is_err()
+    );
+}
+
+fn ndjson(lines: &[serde_json::Value]) -> String {
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
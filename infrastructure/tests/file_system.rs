@@ -0,0 +1,916 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use application::prelude::{
+    Cache, DiskLogEntryDto, FileSystem, LogRepository, OffsetRepository, RejectedLineDto,
+};
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, LogEntry, LogEntryFilter, ReposiotryResult, RepositoryError};
+use flate2::{write::GzEncoder, Compression};
+use infrastructure::prelude::{
+    watch_dir, LinuxFS, MultilineConfig, ParserConfig, Source, SourceConfig,
+};
+use notify::{
+    event::{CreateKind, DataChange, MetadataKind, ModifyKind, RemoveKind, RenameMode},
+    Event, EventKind,
+};
+use skytable::{
+    error::{Error::SkyError, SkyhashError},
+    types::{FromSkyhashBytes, IntoSkyhashBytes},
+    Element, RespCode,
+};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn appended_lines_are_ingested_once() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first", "second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    append(&path, &["third"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn large_file_is_stored_in_batches() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    let messages: Vec<_> = (0..25_000).map(|i| i.to_string()).collect();
+    let messages: Vec<_> = messages.iter().map(String::as_str).collect();
+    append(&path, &messages);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(self::messages(&log_repo), messages);
+    assert_eq!(log_repo.writes.load(Ordering::SeqCst), 3);
+
+    let offset = log_repo.get_offset(path.to_str().unwrap()).await.unwrap();
+    assert_eq!(
+        offset.unwrap().byte_offset as u64,
+        fs::metadata(&path).unwrap().len()
+    );
+}
+
+#[tokio::test]
+async fn invalid_utf8_is_replaced_and_counted() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    write(&path, b"<13>Oct 11 22:14:15 host app: caf\xe9\n");
+    append(&path, &["second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["caf\u{FFFD}", "second"]);
+    assert_eq!(fs.invalid_utf8_lines(), 1);
+}
+
+#[tokio::test]
+async fn partial_line_is_read_once_completed() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    write(&path, "<13>Oct 11 22:14:15 host app: sec");
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first"]);
+
+    write(&path, "ond\n");
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn malformed_line_is_rejected_without_stopping_ingestion() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    write(&path, "<999>Oct 11 22:14:15 host app: broken\n");
+    append(&path, &["second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+
+    let rejected = log_repo.rejected.lock().unwrap();
+    let first_len = "<13>Oct 11 22:14:15 host app: first\n".len() as i64;
+
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].path, path.to_str().unwrap());
+    assert_eq!(rejected[0].byte_offset, first_len);
+    assert_eq!(rejected[0].raw, "<999>Oct 11 22:14:15 host app: broken");
+    assert_eq!(
+        rejected[0].parser,
+        serde_json::json!({ "format": "rfc3164" })
+    );
+}
+
+#[tokio::test]
+async fn oversize_line_is_rejected_in_pieces() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+    let limit = 1024 * 1024;
+    let line = format!(
+        "<13>Oct 11 22:14:15 host app: {}",
+        "a".repeat(limit * 5 / 2)
+    );
+    let (start, rest) = line.split_at(limit * 3 / 2);
+
+    append(&path, &["first"]);
+    write(&path, start);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first"]);
+    assert!(log_repo.rejected.lock().unwrap().is_empty());
+
+    write(&path, format!("{rest}\n"));
+    append(&path, &["second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+
+    let rejected = log_repo.rejected.lock().unwrap();
+    let first_len = "<13>Oct 11 22:14:15 host app: first\n".len() as i64;
+    let offsets: Vec<_> = rejected.iter().map(|line| line.byte_offset).collect();
+    let errors: Vec<_> = rejected.iter().map(|line| line.error.as_str()).collect();
+
+    assert_eq!(
+        offsets,
+        [
+            first_len,
+            first_len + limit as i64,
+            first_len + 2 * limit as i64
+        ]
+    );
+    assert_eq!(
+        errors,
+        [
+            "Line exceeds the limit of 1048576 bytes",
+            "Line exceeds the limit of 1048576 bytes",
+            "Rest of a record over the size limit",
+        ]
+    );
+    assert_eq!(
+        rejected
+            .iter()
+            .map(|line| line.raw.as_str())
+            .collect::<String>(),
+        format!("{line}\n")
+    );
+}
+
+#[tokio::test]
+async fn journal_export_entries_are_read_whole() {
+    let dir = spawn_dir();
+    let path = dir.join("system.journal.export");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        parser: ParserConfig::JournalExport,
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source);
+
+    let mut binary = b"MESSAGE\n".to_vec();
+    binary.extend(12_u64.to_le_bytes());
+    binary.extend(b"first\nsecond\n");
+
+    write(
+        &path,
+        "__REALTIME_TIMESTAMP=1668265565250000\n_HOSTNAME=web-1\n",
+    );
+    write(&path, &binary);
+    write(&path, "\n__REALTIME_TIMESTAMP=1668265566000000\nMESSAGE=th");
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first\nsecond"]);
+    assert_eq!(log_repo.logs.lock().unwrap()[0].host, "web-1");
+
+    write(&path, "ird\n\n");
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first\nsecond", "third"]);
+    assert!(log_repo.rejected.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn journal_entry_larger_than_a_batch_is_read_whole() {
+    let dir = spawn_dir();
+    let path = dir.join("system.journal.export");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        parser: ParserConfig::JournalExport,
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source);
+
+    let message = "core dump line\n".repeat(400_000);
+    let mut binary = b"MESSAGE\n".to_vec();
+    binary.extend((message.len() as u64).to_le_bytes());
+    binary.extend(message.as_bytes());
+
+    write(&path, "__REALTIME_TIMESTAMP=1668265565250000\n");
+    write(&path, &binary);
+    write(
+        &path,
+        "\n\n__REALTIME_TIMESTAMP=1668265566000000\nMESSAGE=second\n\n",
+    );
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), [message.as_str(), "second"]);
+    assert!(log_repo.rejected.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn stack_trace_lines_are_joined_into_one_entry() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_multiline_fs(&dir);
+
+    append(
+        &path,
+        &["first", "java.lang.Error: boom", "at Foo.bar(Foo.java:1)"],
+    );
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first"]);
+
+    append(&path, &["Caused by: oops", "second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(
+        messages(&log_repo),
+        [
+            "first",
+            "java.lang.Error: boom\nat Foo.bar(Foo.java:1)\nCaused by: oops"
+        ]
+    );
+
+    let offset = log_repo.get_offset(path.to_str().unwrap()).await.unwrap();
+    let second = fs::metadata(&path).unwrap().len() - lines(&["second"]).len() as u64;
+    assert_eq!(offset.unwrap().byte_offset as u64, second);
+}
+
+#[tokio::test]
+async fn event_over_the_byte_limit_is_cut() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_multiline_fs(&dir);
+
+    let trace: Vec<_> = (0..60)
+        .map(|i| format!("at Foo.bar(Foo.java:{i})"))
+        .collect();
+    let mut event = vec!["java.lang.Error: boom"];
+    event.extend(trace.iter().map(String::as_str));
+    event.push("second");
+    append(&path, &event);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    let messages = messages(&log_repo);
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("java.lang.Error: boom\nat Foo.bar(Foo.java:0)"));
+    assert!(messages[0].len() <= 1024);
+    assert!(messages[1].starts_with("at Foo.bar(Foo.java:"));
+    assert!(messages[1].ends_with("at Foo.bar(Foo.java:59)"));
+}
+
+#[tokio::test]
+async fn event_larger_than_a_batch_is_read_whole() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        multiline: Some(MultilineConfig {
+            start: None,
+            continuation: Some("^at ".into()),
+            indented: false,
+            flush_timeout_ms: 50,
+            max_lines: 1_000_000,
+            max_bytes: 16 * 1024 * 1024,
+        }),
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source);
+
+    let frame = format!("at {}", "Foo.bar(Foo.java:1) ".repeat(10));
+    let mut event = vec!["java.lang.Error: boom"];
+    event.extend(std::iter::repeat_n(frame.as_str(), 25_000));
+    event.push("second");
+    append(&path, &event);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    let messages = messages(&log_repo);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].lines().count(), 25_001);
+}
+
+#[tokio::test]
+async fn last_event_is_flushed_after_the_timeout() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_multiline_fs(&dir);
+
+    append(&path, &["java.lang.Error: boom", "at Foo.bar(Foo.java:1)"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert!(messages(&log_repo).is_empty());
+    assert!(fs.expired_events().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(fs.expired_events(), vec![path.clone()]);
+
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(
+        messages(&log_repo),
+        ["java.lang.Error: boom\nat Foo.bar(Foo.java:1)"]
+    );
+    assert!(fs.expired_events().is_empty());
+}
+
+#[tokio::test]
+async fn truncated_file_is_read_from_the_beginning() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first", "second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    fs::write(&path, "").unwrap();
+    append(&path, &["after truncation"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "after truncation"]);
+}
+
+#[tokio::test]
+async fn rotated_file_is_finished_before_the_new_one() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let rotated = dir.join("app.log.1");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    // Written right before logrotate renamed the file, without a modify event being handled
+    append(&path, &["second"]);
+    fs::rename(&path, &rotated).unwrap();
+    append(&path, &["third"]);
+
+    fs.handle_event(rename(RenameMode::From, &[&path]))
+        .await
+        .unwrap();
+    fs.handle_event(rename(RenameMode::To, &[&rotated]))
+        .await
+        .unwrap();
+    fs.handle_event(rename(RenameMode::Both, &[&path, &rotated]))
+        .await
+        .unwrap();
+    // Closed once read, it's opened again from its offset if it changes
+    assert_eq!(open_descriptors(&rotated), 0);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    // The writer didn't reopen its file yet
+    append(&rotated, &["fourth"]);
+    fs.handle_event(modify(&rotated)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third", "fourth"]);
+}
+
+#[tokio::test]
+async fn file_renamed_to_excluded_name_is_read_and_closed() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let rotated = dir.join("app.log.1");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        exclude: vec!["*.1".into()],
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    append(&path, &["second"]);
+    fs::rename(&path, &rotated).unwrap();
+    fs.handle_event(rename(RenameMode::Both, &[&path, &rotated]))
+        .await
+        .unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+    assert_eq!(open_descriptors(&rotated), 0);
+}
+
+#[tokio::test]
+async fn replaced_file_is_finished_before_the_new_one() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    // The rotated file was moved out of the watched folder
+    append(&path, &["second"]);
+    fs::rename(&path, dir.with_extension("rotated")).unwrap();
+    append(&path, &["third"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn deleted_file_is_read_to_the_end() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    append(&path, &["second"]);
+    fs::remove_file(&path).unwrap();
+    fs.handle_event(Event::new(EventKind::Remove(RemoveKind::File)).add_path(path.clone()))
+        .await
+        .unwrap();
+
+    append(&path, &["new file"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "new file"]);
+}
+
+#[tokio::test]
+async fn compressed_files_are_ingested_once() {
+    let dir = spawn_dir();
+    let gzip = dir.join("app.log.2.gz");
+    let zstd = dir.join("app.log.3.zst");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&lines(&["first", "second"])).unwrap();
+    fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+    fs::write(&zstd, zstd::encode_all(&lines(&["third"])[..], 0).unwrap()).unwrap();
+
+    fs.handle_event(modify(&gzip)).await.unwrap();
+    fs.handle_event(modify(&zstd)).await.unwrap();
+    fs.handle_event(modify(&gzip)).await.unwrap();
+    fs.handle_event(modify(&zstd)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn partially_written_archive_is_resumed() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log.1.gz");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .unwrap();
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(&lines(&["first"])).unwrap();
+    encoder.flush().unwrap();
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    encoder.write_all(&lines(&["second"])).unwrap();
+    encoder.finish().unwrap();
+    fs.handle_event(modify(&path)).await.unwrap();
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn failed_batch_is_read_again_on_the_next_change() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first", "second"]);
+    log_repo.fail_next.store(true, Ordering::SeqCst);
+    assert!(fs.handle_event(modify(&path)).await.is_err());
+
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn offset_is_restored_after_restart() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+    drop(fs);
+
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source(&dir));
+    append(&path, &["second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn offset_from_cache_is_used_when_missing_in_database() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let log_repo = MemoryLogRepo::default();
+    let cache = MemoryCache::default();
+
+    append(&path, &["first"]);
+    let offset = fs::metadata(&path).unwrap().len();
+    cache
+        .set(path.to_str().unwrap(), offset.to_string())
+        .unwrap();
+
+    let fs = LinuxFS::new(Some(cache), log_repo.clone(), source(&dir));
+    append(&path, &["second"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["second"]);
+}
+
+#[tokio::test]
+async fn rescan_catches_up_with_files_changed_while_down() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let nested = dir.join("host").join("auth.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(modify(&path)).await.unwrap();
+    drop(fs);
+
+    append(&path, &["second"]);
+    fs::create_dir_all(nested.parent().unwrap()).unwrap();
+    append(&nested, &["third"]);
+
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source(&dir));
+    fs.rescan(&dir).await.unwrap();
+    fs.handle_event(modify(&path)).await.unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn source_globs_and_overrides_are_applied() {
+    let dir = spawn_dir();
+    let included = dir.join("web-01").join("app.log");
+    let excluded = dir.join("web-01").join("debug.log");
+    let other = dir.join("web-01").join("app.txt");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        include: vec!["*/*.log".into()],
+        exclude: vec!["*/debug.log".into()],
+        host_from_dir: true,
+        source: Some("remote".into()),
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let fs = LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source);
+
+    fs::create_dir_all(included.parent().unwrap()).unwrap();
+
+    for path in [&included, &excluded, &other] {
+        append(path, &[path.file_name().unwrap().to_str().unwrap()]);
+        fs.handle_event(modify(path)).await.unwrap();
+    }
+
+    let logs = log_repo.logs.lock().unwrap();
+
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].message, "app.log");
+    assert_eq!(logs[0].host, "web-01");
+    assert_eq!(logs[0].source, "remote");
+}
+
+#[tokio::test]
+async fn events_of_polling_watcher_are_handled() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let (fs, log_repo) = spawn_fs(&dir);
+
+    append(&path, &["first"]);
+    fs.handle_event(Event::new(EventKind::Create(CreateKind::Any)).add_path(path.clone()))
+        .await
+        .unwrap();
+
+    append(&path, &["second"]);
+    fs.handle_event(
+        Event::new(EventKind::Modify(ModifyKind::Metadata(
+            MetadataKind::WriteTime,
+        )))
+        .add_path(path.clone()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(messages(&log_repo), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn polled_source_ingests_appended_and_created_files() {
+    let dir = spawn_dir();
+    let path = dir.join("app.log");
+    let created = dir.join("created.log");
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        poll_interval_ms: Some(20),
+        poll_compare_contents: true,
+        ..source_config(&dir)
+    })
+    .unwrap();
+    let poll = source.poll_config();
+
+    append(&path, &["first"]);
+
+    let fs = Arc::new(LinuxFS::new(None::<MemoryCache>, log_repo.clone(), source));
+    let watcher = watch_dir(dir.to_str().unwrap(), poll, fs).unwrap();
+    assert_eq!(watcher.path(), dir.to_str().unwrap());
+
+    wait_for_messages(&log_repo, 1).await;
+    append(&path, &["second"]);
+    wait_for_messages(&log_repo, 2).await;
+    append(&created, &["third"]);
+    wait_for_messages(&log_repo, 3).await;
+
+    assert_eq!(messages(&log_repo), ["first", "second", "third"]);
+}
+
+type TestFS = LinuxFS<MemoryCache, MemoryLogRepo>;
+
+fn spawn_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ferri-log-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).expect("Cannot create test directory");
+    dir
+}
+
+fn spawn_fs(dir: &Path) -> (TestFS, MemoryLogRepo) {
+    let log_repo = MemoryLogRepo::default();
+
+    (LinuxFS::new(None, log_repo.clone(), source(dir)), log_repo)
+}
+
+fn spawn_multiline_fs(dir: &Path) -> (TestFS, MemoryLogRepo) {
+    let log_repo = MemoryLogRepo::default();
+    let source = Source::new(SourceConfig {
+        multiline: Some(MultilineConfig {
+            start: None,
+            continuation: Some("^(at |Caused by:)".into()),
+            indented: false,
+            flush_timeout_ms: 50,
+            max_lines: 100,
+            max_bytes: 1024,
+        }),
+        ..source_config(dir)
+    })
+    .unwrap();
+
+    (LinuxFS::new(None, log_repo.clone(), source), log_repo)
+}
+
+fn source(dir: &Path) -> Source {
+    Source::new(source_config(dir)).unwrap()
+}
+
+fn source_config(dir: &Path) -> SourceConfig {
+    SourceConfig {
+        path: dir.to_str().unwrap().into(),
+        include: Vec::new(),
+        exclude: Vec::new(),
+        parser: ParserConfig::Rfc3164,
+        parsers: Vec::new(),
+        host: None,
+        host_from_dir: false,
+        source: None,
+        poll_interval_ms: None,
+        poll_compare_contents: false,
+        multiline: None,
+        timezone: None,
+        timestamp_formats: Vec::new(),
+    }
+}
+
+fn messages(log_repo: &MemoryLogRepo) -> Vec<String> {
+    log_repo
+        .logs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|log| log.message.clone())
+        .collect()
+}
+
+async fn wait_for_messages(log_repo: &MemoryLogRepo, count: usize) {
+    for _ in 0..250 {
+        if log_repo.logs.lock().unwrap().len() >= count {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    panic!("Expected {count} messages, got {:?}", messages(log_repo));
+}
+
+/// Number of descriptors of the process open on the file.
+fn open_descriptors(path: &Path) -> usize {
+    fs::read_dir("/proc/self/fd")
+        .unwrap()
+        .filter_map(|entry| fs::read_link(entry.ok()?.path()).ok())
+        .filter(|target| target == path)
+        .count()
+}
+
+fn append(path: &Path, lines: &[&str]) {
+    write(path, self::lines(lines));
+}
+
+fn write(path: &Path, content: impl AsRef<[u8]>) {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .expect("Cannot open test file");
+
+    file.write_all(content.as_ref()).unwrap();
+}
+
+fn lines(lines: &[&str]) -> Vec<u8> {
+    lines
+        .iter()
+        .flat_map(|line| format!("<13>Oct 11 22:14:15 host app: {line}\n").into_bytes())
+        .collect()
+}
+
+fn modify(path: &Path) -> Event {
+    Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Any))).add_path(path.into())
+}
+
+/// Rename events of one rename share the tracker, as reported by inotify.
+fn rename(mode: RenameMode, paths: &[&Path]) -> Event {
+    paths.iter().fold(
+        Event::new(EventKind::Modify(ModifyKind::Name(mode))).set_tracker(1),
+        |event, path| event.add_path(path.to_path_buf()),
+    )
+}
+
+#[derive(Default)]
+struct MemoryCache {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl Cache for MemoryCache {
+    fn get<T: FromSkyhashBytes>(&self, key: &str) -> Result<T, skytable::error::Error> {
+        match self.entries.lock().unwrap().get(key) {
+            Some(value) => T::from_element(Element::Binstr(value.clone())),
+            None => Err(SkyError(SkyhashError::Code(RespCode::NotFound))),
+        }
+    }
+
+    fn set<T: IntoSkyhashBytes>(
+        &self,
+        key: &str,
+        value: T,
+    ) -> Result<bool, skytable::error::Error> {
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.contains_key(key) {
+            return Ok(false);
+        }
+
+        entries.insert(key.into(), value.as_bytes());
+        Ok(true)
+    }
+
+    fn update<T: IntoSkyhashBytes>(
+        &self,
+        key: &str,
+        value: T,
+    ) -> Result<(), skytable::error::Error> {
+        match self.entries.lock().unwrap().get_mut(key) {
+            Some(entry) => {
+                *entry = value.as_bytes();
+                Ok(())
+            }
+            None => Err(SkyError(SkyhashError::Code(RespCode::NotFound))),
+        }
+    }
+
+    fn del(&self, key: &str) -> Result<u64, skytable::error::Error> {
+        Ok(self.entries.lock().unwrap().remove(key).map_or(0, |_| 1))
+    }
+}
+
+#[derive(Default, Clone)]
+struct MemoryLogRepo {
+    logs: Arc<Mutex<Vec<DiskLogEntryDto>>>,
+    offsets: Arc<Mutex<HashMap<String, FileOffset>>>,
+    rejected: Arc<Mutex<Vec<RejectedLineDto>>>,
+    /// Fails the next write, as if the database went away.
+    fail_next: Arc<AtomicBool>,
+    /// Number of the successful writes.
+    writes: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl LogRepository for MemoryLogRepo {
+    async fn get_log_by_id(&self, _id: Uuid) -> ReposiotryResult<LogEntry> {
+        unimplemented!()
+    }
+
+    async fn get_logs_by_filter(&self, _filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn create_log(&self, disk_log_dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
+        self.logs.lock().unwrap().push(disk_log_dto);
+        Ok(Uuid::new_v4())
+    }
+
+    async fn create_logs(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        let ids = disk_log_dtos.iter().map(|_| Uuid::new_v4()).collect();
+        self.logs.lock().unwrap().extend(disk_log_dtos);
+        Ok(ids)
+    }
+
+    async fn create_logs_with_offset(
+        &self,
+        disk_log_dtos: Vec<DiskLogEntryDto>,
+        rejected_line_dtos: Vec<RejectedLineDto>,
+        offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        if self.fail_next.swap(false, Ordering::SeqCst) {
+            return Err(RepositoryError::Database(sqlx::Error::PoolClosed));
+        }
+
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.rejected.lock().unwrap().extend(rejected_line_dtos);
+        self.set_offset(offset).await?;
+        self.create_logs(disk_log_dtos).await
+    }
+
+    async fn delete_log(&self, _id: Uuid) -> ReposiotryResult<()> {
+        unimplemented!()
+    }
+}
+
+#[async_trait]
+impl OffsetRepository for MemoryLogRepo {
+    async fn get_offset(&self, path: &str) -> ReposiotryResult<Option<FileOffset>> {
+        Ok(self.offsets.lock().unwrap().get(path).cloned())
+    }
+
+    async fn set_offset(&self, offset: FileOffset) -> ReposiotryResult<()> {
+        self.offsets
+            .lock()
+            .unwrap()
+            .insert(offset.path.clone(), offset);
+        Ok(())
+    }
+
+    async fn delete_offset(&self, path: &str) -> ReposiotryResult<()> {
+        self.offsets.lock().unwrap().remove(path);
+        Ok(())
+    }
+}
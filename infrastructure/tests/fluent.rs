@@ -0,0 +1,284 @@
+use std::{
+    io::Write,
+    net::TcpListener as StdTcpListener,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use application::prelude::{DiskLogEntryDto, LogRepository, RejectedLineDto};
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, LogEntry, LogEntryFilter, ReposiotryResult, RepositoryError};
+use flate2::{write::GzEncoder, Compression};
+use infrastructure::prelude::{decode_forward, decode_msgpack, listen_fluent};
+use rmp::encode;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use uuid::Uuid;
+
+#[test]
+fn decode_message_mode() {
+    let mut message = Vec::new();
+    encode::write_array_len(&mut message, 3).unwrap();
+    encode::write_str(&mut message, "app.web").unwrap();
+    event_time(&mut message, 1_668_265_565, 250_000_000);
+    record(
+        &mut message,
+        &[("log", "GET / 200"), ("level", "WARN"), ("pod", "web-1")],
+    );
+
+    let (value, len) = decode_msgpack(&message).unwrap().unwrap();
+    let forward = decode_forward(value).expect("Cannot decode forward message");
+
+    assert_eq!(len, message.len());
+    assert_eq!(forward.chunk, None);
+    assert_eq!(forward.entries.len(), 1);
+
+    let log = &forward.entries[0];
+    assert_eq!(log.timestamp, "2022-11-12T15:06:05.250Z");
+    assert_eq!(log.source, "app.web");
+    assert_eq!(log.severity, "warning");
+    assert_eq!(log.message, "GET / 200");
+    assert_eq!(log.attributes["pod"], "web-1");
+    assert!(!log.attributes.contains_key("log"));
+}
+
+#[test]
+fn decode_forward_and_compressed_packed_forward_modes() {
+    let mut entries = Vec::new();
+    entry(&mut entries, "first");
+    entry(&mut entries, "second");
+
+    let mut forward = Vec::new();
+    encode::write_array_len(&mut forward, 3).unwrap();
+    encode::write_str(&mut forward, "app").unwrap();
+    encode::write_array_len(&mut forward, 2).unwrap();
+    forward.extend(&entries);
+    record(&mut forward, &[("chunk", "c1")]);
+
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(&entries).unwrap();
+    let mut packed = Vec::new();
+    encode::write_array_len(&mut packed, 3).unwrap();
+    encode::write_str(&mut packed, "app").unwrap();
+    encode::write_bin(&mut packed, &gzip.finish().unwrap()).unwrap();
+    record(&mut packed, &[("chunk", "c2"), ("compressed", "gzip")]);
+
+    for (message, chunk) in [(forward, "c1"), (packed, "c2")] {
+        let (value, _) = decode_msgpack(&message).unwrap().unwrap();
+        let forward = decode_forward(value).expect("Cannot decode forward message");
+
+        assert_eq!(forward.chunk.as_deref(), Some(chunk));
+        let messages: Vec<_> = forward.entries.iter().map(|log| &log.message).collect();
+        assert_eq!(messages, ["first", "second"]);
+    }
+}
+
+#[test]
+fn incomplete_message_waits_for_the_rest() {
+    let mut message = Vec::new();
+    encode::write_array_len(&mut message, 2).unwrap();
+    encode::write_str(&mut message, "app").unwrap();
+    encode::write_array_len(&mut message, 1).unwrap();
+    entry(&mut message, "first");
+
+    for len in 0..message.len() {
+        assert!(decode_msgpack(&message[..len]).unwrap().is_none());
+    }
+
+    assert!(decode_msgpack(&[0x91; 100]).is_err());
+}
+
+#[tokio::test]
+async fn message_is_acknowledged_once_stored() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_address();
+    listen_fluent(&address, log_repo.clone()).await.unwrap();
+
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+    stream.write_all(&chunked_message("c1")).await.unwrap();
+
+    let mut ack = vec![0; 64];
+    let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut ack))
+        .await
+        .expect("No acknowledgement received")
+        .unwrap();
+    let (ack, _) = decode_msgpack(&ack[..n]).unwrap().unwrap();
+
+    assert_eq!(ack["ack"].as_str(), Some("c1"));
+    assert_eq!(log_repo.logs.lock().unwrap().len(), 1);
+
+    log_repo.fail_next.store(true, Ordering::SeqCst);
+    stream.write_all(&chunked_message("c2")).await.unwrap();
+
+    let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut [0; 64]))
+        .await
+        .expect("Connection wasn't closed")
+        .unwrap_or(0);
+
+    assert_eq!(n, 0);
+    assert_eq!(log_repo.logs.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn message_split_over_writes_is_acknowledged() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_address();
+    listen_fluent(&address, log_repo.clone()).await.unwrap();
+
+    let message = chunked_message("c1");
+    let (first, rest) = message.split_at(message.len() / 3);
+
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+    stream.write_all(first).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    stream.write_all(rest).await.unwrap();
+
+    let mut ack = vec![0; 64];
+    let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut ack))
+        .await
+        .expect("No acknowledgement received")
+        .unwrap();
+    let (ack, _) = decode_msgpack(&ack[..n]).unwrap().unwrap();
+
+    assert_eq!(ack["ack"].as_str(), Some("c1"));
+    assert_eq!(log_repo.logs.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_input_closes_the_connection() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_address();
+    listen_fluent(&address, log_repo.clone()).await.unwrap();
+
+    let mut not_forward = Vec::new();
+    encode::write_str(&mut not_forward, "app").unwrap();
+    not_forward.extend(chunked_message("c1"));
+
+    for input in [not_forward, vec![0xc1]] {
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        stream.write_all(&input).await.unwrap();
+
+        assert_closed(&mut stream).await;
+    }
+
+    assert!(log_repo.logs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn oversize_message_closes_the_connection() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_address();
+    listen_fluent(&address, log_repo.clone()).await.unwrap();
+
+    // The tag alone is over the limit, the connection is closed before it's complete
+    let len = 32 * 1024 * 1024 + 1;
+    let mut message = Vec::new();
+    encode::write_array_len(&mut message, 2).unwrap();
+    encode::write_str_len(&mut message, len as u32).unwrap();
+    message.resize(message.len() + len, b'a');
+
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+    // The connection may be closed while the message is still written
+    let _ = stream.write_all(&message).await;
+
+    assert_closed(&mut stream).await;
+}
+
+async fn assert_closed(stream: &mut TcpStream) {
+    let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut [0; 64]))
+        .await
+        .expect("Connection wasn't closed")
+        .unwrap_or(0);
+
+    assert_eq!(n, 0);
+}
+
+fn chunked_message(chunk: &str) -> Vec<u8> {
+    let mut message = Vec::new();
+    encode::write_array_len(&mut message, 3).unwrap();
+    encode::write_str(&mut message, "app").unwrap();
+    encode::write_array_len(&mut message, 1).unwrap();
+    entry(&mut message, "first");
+    record(&mut message, &[("chunk", chunk)]);
+    message
+}
+
+fn entry(buffer: &mut Vec<u8>, message: &str) {
+    encode::write_array_len(buffer, 2).unwrap();
+    encode::write_uint(buffer, 1_668_265_565).unwrap();
+    record(buffer, &[("message", message)]);
+}
+
+fn event_time(buffer: &mut Vec<u8>, seconds: u32, nanos: u32) {
+    encode::write_ext_meta(buffer, 8, 0).unwrap();
+    buffer.extend(seconds.to_be_bytes());
+    buffer.extend(nanos.to_be_bytes());
+}
+
+fn record(buffer: &mut Vec<u8>, fields: &[(&str, &str)]) {
+    encode::write_map_len(buffer, fields.len() as u32).unwrap();
+
+    for (key, value) in fields {
+        encode::write_str(buffer, key).unwrap();
+        encode::write_str(buffer, value).unwrap();
+    }
+}
+
+fn free_address() -> String {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+#[derive(Clone, Default)]
+struct MemoryLogRepo {
+    logs: Arc<Mutex<Vec<DiskLogEntryDto>>>,
+    /// Fails the next write, as if the database went away.
+    fail_next: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl LogRepository for MemoryLogRepo {
+    async fn get_log_by_id(&self, _id: Uuid) -> ReposiotryResult<LogEntry> {
+        unimplemented!()
+    }
+
+    async fn get_logs_by_filter(&self, _filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn create_log(&self, _dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
+        unimplemented!()
+    }
+
+    async fn create_logs(&self, dtos: Vec<DiskLogEntryDto>) -> ReposiotryResult<Vec<Uuid>> {
+        if self.fail_next.swap(false, Ordering::SeqCst) {
+            return Err(RepositoryError::Database(sqlx::Error::PoolClosed));
+        }
+
+        let ids = dtos.iter().map(|_| Uuid::new_v4()).collect();
+        self.logs.lock().unwrap().extend(dtos);
+        Ok(ids)
+    }
+
+    async fn create_logs_with_offset(
+        &self,
+        _dtos: Vec<DiskLogEntryDto>,
+        _rejected_dtos: Vec<RejectedLineDto>,
+        _offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        unimplemented!()
+    }
+
+    async fn delete_log(&self, _id: Uuid) -> ReposiotryResult<()> {
+        unimplemented!()
+    }
+}
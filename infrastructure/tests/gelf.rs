@@ -0,0 +1,155 @@
+use std::{io::Write, net::SocketAddr};
+
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use infrastructure::prelude::{decode_gelf, ChunkAssembler};
+use serde_json::json;
+
+const PEER: SocketAddr = SocketAddr::V4(std::net::SocketAddrV4::new(
+    std::net::Ipv4Addr::new(10, 0, 0, 1),
+    12201,
+));
+
+#[test]
+fn decode_gelf_message_with_additional_fields() {
+    let message = json!({
+        "version": "1.1",
+        "host": "docker-1",
+        "short_message": "GET / 200",
+        "full_message": "GET / 200\nUser-Agent: curl",
+        "timestamp": 1668265565.25,
+        "level": 3,
+        "_tag": "nginx",
+        "_container_id": "4f2c",
+    });
+
+    let log = decode_gelf(message.to_string().as_bytes()).expect("Cannot decode GELF message");
+
+    assert_eq!(log.timestamp, "2022-11-12T15:06:05.250Z");
+    assert_eq!(log.host, "docker-1");
+    assert_eq!(log.severity, "err");
+    assert_eq!(log.source, "nginx");
+    assert_eq!(log.message, "GET / 200");
+    assert_eq!(log.attributes["container_id"], "4f2c");
+    assert_eq!(
+        log.attributes["full_message"],
+        "GET / 200\nUser-Agent: curl"
+    );
+    assert!(!log.attributes.contains_key("version"));
+}
+
+#[test]
+fn decode_compressed_gelf_messages() {
+    let message =
+        json!({ "version": "1.1", "host": "docker-1", "short_message": "started" }).to_string();
+
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(message.as_bytes()).unwrap();
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(message.as_bytes()).unwrap();
+
+    for payload in [gzip.finish().unwrap(), zlib.finish().unwrap()] {
+        let log = decode_gelf(&payload).expect("Cannot decode GELF message");
+
+        assert_eq!(log.message, "started");
+        assert_eq!(log.severity, "alert");
+    }
+}
+
+#[test]
+fn reject_gelf_message_without_required_fields() {
+    assert!(decode_gelf(br#"{"version":"1.1","host":"docker-1"}"#).is_err());
+    assert!(decode_gelf(br#"{"host":"docker-1","short_message":"x","level":9}"#).is_err());
+}
+
+#[test]
+fn reassemble_chunked_gelf_message() {
+    let mut assembler = ChunkAssembler::default();
+    let message = b"{\"host\":\"docker-1\",\"short_message\":\"chunked\"}";
+    let (first, second) = message.split_at(20);
+
+    assert_eq!(assembler.push(PEER, &chunk(7, 1, 2, second)).unwrap(), None);
+    assert_eq!(assembler.push(PEER, &chunk(7, 1, 2, second)).unwrap(), None);
+    assert_eq!(
+        assembler.push(PEER, &chunk(8, 0, 2, b"other")).unwrap(),
+        None
+    );
+    assert_eq!(assembler.pending(), 2);
+
+    let whole = assembler.push(PEER, &chunk(7, 0, 2, first)).unwrap();
+
+    assert_eq!(whole.as_deref(), Some(&message[..]));
+    assert_eq!(assembler.pending(), 1);
+    assert_eq!(decode_gelf(&whole.unwrap()).unwrap().message, "chunked");
+}
+
+#[test]
+fn chunks_of_other_senders_dont_complete_a_message() {
+    let mut assembler = ChunkAssembler::default();
+    let other: SocketAddr = "10.0.0.2:12201".parse().unwrap();
+
+    assert_eq!(
+        assembler.push(PEER, &chunk(7, 0, 2, b"first")).unwrap(),
+        None
+    );
+    assert_eq!(
+        assembler.push(other, &chunk(7, 1, 2, b"forged")).unwrap(),
+        None
+    );
+    assert_eq!(assembler.pending(), 2);
+
+    let whole = assembler.push(PEER, &chunk(7, 1, 2, b"second")).unwrap();
+    assert_eq!(whole.as_deref(), Some(&b"firstsecond"[..]));
+}
+
+#[test]
+fn oldest_incomplete_messages_are_evicted() {
+    let mut assembler = ChunkAssembler::default();
+
+    for id in 0..1025 {
+        assembler.push(PEER, &chunk(id, 0, 2, b"x")).unwrap();
+    }
+    assert_eq!(assembler.pending(), 1024);
+    assert!(assembler
+        .push(PEER, &chunk(1, 1, 2, b"y"))
+        .unwrap()
+        .is_some());
+    assert_eq!(assembler.push(PEER, &chunk(0, 1, 2, b"y")).unwrap(), None);
+
+    // Chunks of 60 KiB, the oldest messages are dropped past 32 MiB
+    let mut assembler = ChunkAssembler::default();
+    let payload = vec![b'x'; 60 * 1024];
+
+    for id in 0..600 {
+        assembler.push(PEER, &chunk(id, 0, 2, &payload)).unwrap();
+    }
+    assert_eq!(assembler.pending(), 32 * 1024 / 60);
+    assert_eq!(assembler.push(PEER, &chunk(0, 1, 2, b"y")).unwrap(), None);
+    assert!(assembler
+        .push(PEER, &chunk(599, 1, 2, b"y"))
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn reject_invalid_gelf_chunks() {
+    let mut assembler = ChunkAssembler::default();
+
+    assert!(assembler.push(PEER, &chunk(1, 2, 2, b"x")).is_err());
+    assert!(assembler.push(PEER, &chunk(1, 0, 129, b"x")).is_err());
+    assert!(assembler.push(PEER, &[0x1e, 0x0f, 1]).is_err());
+    assert_eq!(
+        assembler.push(PEER, b"{}").unwrap().as_deref(),
+        Some(&b"{}"[..])
+    );
+}
+
+fn chunk(id: u64, number: u8, count: u8, payload: &[u8]) -> Vec<u8> {
+    let mut chunk = vec![0x1e, 0x0f];
+    chunk.extend(id.to_be_bytes());
+    chunk.extend([number, count]);
+    chunk.extend(payload);
+    chunk
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use application::prelude::{DiskLogEntryDto, LogRepository, OffsetRepository, RejectedLineDto};
+use domain::prelude::FileOffset;
+use infrastructure::prelude::PgLogRepo;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn successfully_create_log_entries_in_database() -> Result<()> {
+    let log_repo = spawn_repo().await;
+
+    let log_dto = DiskLogEntryDto {
+        facility: "Repo test".into(),
+        host: "localhost".into(),
+        message: "Sample message".into(),
+        severity: "Info".into(),
+        source: "Unit test".into(),
+        syslog_tag: "Sample tag".into(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+
+    let ids = log_repo.create_logs(vec![log_dto; 10_000]).await?;
+    let logs = log_repo.get_all_logs().await?;
+
+    assert_eq!(ids.len(), 10_000);
+    assert_eq!(logs.len(), 10_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_batch_stores_neither_entries_nor_offset() -> Result<()> {
+    let log_repo = spawn_repo().await;
+
+    let log_dto = DiskLogEntryDto {
+        facility: "user".into(),
+        host: "localhost".into(),
+        message: "Sample message".into(),
+        severity: "info".into(),
+        source: "app".into(),
+        syslog_tag: "app:".into(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+    let offset = |byte_offset| FileOffset {
+        path: "/var/log/app.log".into(),
+        dev: 1,
+        ino: 2,
+        byte_offset,
+        complete: false,
+    };
+
+    log_repo
+        .create_logs_with_offset(vec![log_dto.clone()], Vec::new(), offset(100))
+        .await?;
+
+    // Postgres rejects the NUL byte, so the transaction fails after the entries are inserted
+    let rejected_line = RejectedLineDto {
+        path: "/var/log/app.log".into(),
+        byte_offset: 100,
+        raw: "invalid\0line".into(),
+        error: "Invalid line".into(),
+        parser: serde_json::Value::Null,
+    };
+    let result = log_repo
+        .create_logs_with_offset(vec![log_dto; 3], vec![rejected_line], offset(200))
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(log_repo.get_all_logs().await?.len(), 1);
+    assert_eq!(
+        log_repo.get_offset("/var/log/app.log").await?,
+        Some(offset(100))
+    );
+
+    Ok(())
+}
+
+async fn spawn_repo() -> PgLogRepo {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL has to be set");
+    let database_name = Uuid::new_v4().to_string();
+
+    let mut connection = PgConnection::connect(&url)
+        .await
+        .expect("Failed to connect to Postgres instance");
+    connection
+        .execute(format!(r#"CREATE DATABASE "{database_name}";"#).as_str())
+        .await
+        .expect("Failed to create database");
+
+    let (server_url, _) = url.rsplit_once('/').expect("Invalid DATABASE_URL");
+    let pool = PgPool::connect(&format!("{server_url}/{database_name}"))
+        .await
+        .expect("Failed to connect to Postgres");
+
+    sqlx::migrate!("../migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    PgLogRepo::new(pool)
+}
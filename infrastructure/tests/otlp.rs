@@ -0,0 +1,131 @@
+use infrastructure::prelude::{decode_otlp_logs, OtlpEncoding};
+use opentelemetry_proto::tonic::{
+    collector::logs::v1::ExportLogsServiceRequest,
+    common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+    logs::v1::{LogRecord, ResourceLogs, ScopeLogs},
+    resource::v1::Resource,
+};
+use prost::Message;
+use serde_json::json;
+
+#[test]
+fn decode_protobuf_export() {
+    let request = ExportLogsServiceRequest {
+        resource_logs: vec![ResourceLogs {
+            resource: Some(Resource {
+                attributes: vec![
+                    string_attribute("service.name", "checkout"),
+                    string_attribute("host.name", "web-1"),
+                ],
+                ..Default::default()
+            }),
+            scope_logs: vec![ScopeLogs {
+                scope: Some(InstrumentationScope {
+                    name: "checkout.orders".into(),
+                    ..Default::default()
+                }),
+                log_records: vec![LogRecord {
+                    time_unix_nano: 1_668_265_565_000_000_000,
+                    severity_number: 17,
+                    severity_text: "ERROR".into(),
+                    body: Some(string_value("payment failed")),
+                    attributes: vec![string_attribute("order.id", "42")],
+                    trace_id: vec![0x5b; 16],
+                    span_id: vec![0; 8],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    };
+
+    let logs = decode_otlp_logs(&request.encode_to_vec(), OtlpEncoding::Protobuf)
+        .expect("Cannot decode OTLP export");
+
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].timestamp, "2022-11-12T15:06:05Z");
+    assert_eq!(logs[0].host, "web-1");
+    assert_eq!(logs[0].source, "checkout");
+    assert_eq!(logs[0].severity, "err");
+    assert_eq!(logs[0].message, "payment failed");
+    assert_eq!(logs[0].trace_id.as_deref(), Some("5b".repeat(16).as_str()));
+    assert_eq!(logs[0].span_id, None);
+    assert_eq!(logs[0].attributes["order.id"], "42");
+    assert_eq!(logs[0].attributes["otel.scope.name"], "checkout.orders");
+    assert_eq!(logs[0].resource["service.name"], "checkout");
+}
+
+#[test]
+fn decode_json_export() {
+    let body = json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": "worker" } }]
+            },
+            "scopeLogs": [{
+                "logRecords": [{
+                    "timeUnixNano": "1668265565000000000",
+                    "severityText": "Notice",
+                    "body": { "kvlistValue": { "values": [
+                        { "key": "job", "value": { "intValue": "7" } }
+                    ] } },
+                    "traceId": "5b8efff798038103d269b633813fc60c",
+                    "spanId": "eee19b7ec3c1b174"
+                }]
+            }]
+        }]
+    });
+
+    let logs = decode_otlp_logs(body.to_string().as_bytes(), OtlpEncoding::Json)
+        .expect("Cannot decode OTLP export");
+
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].severity, "notice");
+    assert_eq!(logs[0].message, r#"{"job":7}"#);
+    assert_eq!(logs[0].syslog_tag, "worker:");
+    assert_eq!(
+        logs[0].trace_id.as_deref(),
+        Some("5b8efff798038103d269b633813fc60c")
+    );
+    assert_eq!(logs[0].span_id.as_deref(), Some("eee19b7ec3c1b174"));
+}
+
+#[test]
+fn severity_text_is_mapped_onto_syslog_severity() {
+    let records: Vec<_> = ["WARN", "fatal", "Trace", "verbose", ""]
+        .iter()
+        .map(|text| json!({ "timeUnixNano": "1668265565000000000", "severityText": text }))
+        .collect();
+    let body = json!({ "resourceLogs": [{ "scopeLogs": [{ "logRecords": records }] }] });
+
+    let logs = decode_otlp_logs(body.to_string().as_bytes(), OtlpEncoding::Json)
+        .expect("Cannot decode OTLP export");
+
+    let severities: Vec<_> = logs.iter().map(|log| log.severity.as_str()).collect();
+    assert_eq!(severities, ["warning", "crit", "debug", "notice", "notice"]);
+}
+
+#[test]
+fn reject_malformed_export() {
+    assert!(decode_otlp_logs(b"\xff\xff", OtlpEncoding::Protobuf).is_err());
+    assert!(decode_otlp_logs(b"{", OtlpEncoding::Json).is_err());
+    assert_eq!(
+        OtlpEncoding::from_content_type("application/json; charset=utf-8"),
+        Some(OtlpEncoding::Json)
+    );
+    assert_eq!(OtlpEncoding::from_content_type("text/plain"), None);
+}
+
+fn string_value(value: &str) -> AnyValue {
+    AnyValue {
+        value: Some(any_value::Value::StringValue(value.into())),
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.into(),
+        value: Some(string_value(value)),
+    }
+}
@@ -0,0 +1,274 @@
+use application::prelude::LogParser;
+use chrono::{Datelike, Duration, FixedOffset, Utc};
+use infrastructure::prelude::{
+    decode_log_batch, parse_line, JournalJsonParser, ParserConfig, ParserSelector, RegexParser,
+    Rfc3164Parser, Rfc5424Parser, RsyslogJsonParser, SyslogParser, TimestampNormalizer,
+};
+use std::path::Path;
+
+#[test]
+fn successfully_parse_rsyslog_json_line() {
+    let line = r#"{"timestamp":"2022-11-12T16:06:05+01:00","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"nginx:","source":"nginx","message":"started","client_identity":"admin"}"#;
+
+    let log = RsyslogJsonParser
+        .parse(line)
+        .expect("Cannot parse JSON line");
+
+    assert_eq!(log.host, "web-1");
+    assert_eq!(log.message, "started");
+    // Only an authenticated input sets the identity
+    assert_eq!(log.client_identity, None);
+}
+
+#[test]
+fn reject_rsyslog_json_line_with_invalid_timestamp() {
+    let line = r#"{"timestamp":"yesterday","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"nginx:","source":"nginx","message":"started"}"#;
+
+    assert!(parse_line(&RsyslogJsonParser, line, &TimestampNormalizer::default()).is_err());
+}
+
+#[test]
+fn successfully_parse_rfc3164_message() {
+    let line = "<34>Oct 11 22:14:15 mymachine su[42]: 'su root' failed for lonvick on /dev/pts/8";
+
+    let log = Rfc3164Parser
+        .parse(line)
+        .expect("Cannot parse RFC 3164 message");
+
+    assert_eq!(log.host, "mymachine");
+    assert_eq!(log.facility, "auth");
+    assert_eq!(log.severity, "crit");
+    assert_eq!(log.syslog_tag, "su[42]:");
+    assert_eq!(log.source, "su");
+    assert_eq!(log.message, "'su root' failed for lonvick on /dev/pts/8");
+}
+
+#[test]
+fn successfully_parse_rfc3164_message_without_hostname() {
+    let line = "<13>Feb  5 17:32:18 cron[55]: job done";
+
+    let log = Rfc3164Parser
+        .parse(line)
+        .expect("Cannot parse RFC 3164 message");
+
+    assert_eq!(log.host, "");
+    assert_eq!(log.source, "cron");
+    assert_eq!(log.message, "job done");
+}
+
+#[test]
+fn successfully_parse_rfc5424_message_with_structured_data() {
+    let line = r#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog 8710 ID47 [exampleSDID@32473 iut="3" eventSource="Application"][examplePriority@32473 class="high"] An application event"#;
+
+    let log = Rfc5424Parser
+        .parse(line)
+        .expect("Cannot parse RFC 5424 message");
+
+    assert_eq!(log.timestamp, "2003-10-11T22:14:15.003+00:00");
+    assert_eq!(log.host, "mymachine.example.com");
+    assert_eq!(log.facility, "local4");
+    assert_eq!(log.severity, "notice");
+    assert_eq!(log.syslog_tag, "evntslog[8710]:");
+    assert_eq!(log.app_name.as_deref(), Some("evntslog"));
+    assert_eq!(log.proc_id.as_deref(), Some("8710"));
+    assert_eq!(log.msg_id.as_deref(), Some("ID47"));
+    assert_eq!(log.attributes["exampleSDID@32473"]["iut"], "3");
+    assert_eq!(log.attributes["examplePriority@32473"]["class"], "high");
+    assert_eq!(log.message, "An application event");
+}
+
+#[test]
+fn syslog_parser_detects_message_format() {
+    let rfc5424 = SyslogParser
+        .parse("<34>1 2003-10-11T22:14:15.003Z host su - ID47 - failed")
+        .expect("Cannot parse RFC 5424 message");
+    let rfc3164 = SyslogParser
+        .parse("<34>Oct 11 22:14:15 host su: failed")
+        .expect("Cannot parse RFC 3164 message");
+
+    assert_eq!(rfc5424.msg_id.as_deref(), Some("ID47"));
+    assert_eq!(rfc3164.msg_id, None);
+    assert_eq!(rfc5424.message, rfc3164.message);
+}
+
+#[test]
+fn successfully_parse_line_with_grok_template() {
+    let parser = RegexParser::from_grok(
+        r#"%{IPORHOST:host} - %{NOTSPACE:user} \[%{HTTPDATE:timestamp}\] %{QS:request} %{INT:status} %{GREEDYDATA}"#,
+    )
+    .expect("Cannot build grok parser");
+
+    let log = parser
+        .parse(r#"10.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /a.gif HTTP/1.0" 200 2326"#)
+        .expect("Cannot parse access log line");
+
+    assert_eq!(log.host, "10.0.0.1");
+    assert_eq!(log.timestamp, "2000-10-10T13:55:36-07:00");
+    assert_eq!(log.attributes["user"], "frank");
+    assert_eq!(log.attributes["status"], "200");
+    assert!(log.message.starts_with("10.0.0.1"));
+}
+
+#[test]
+fn reject_line_not_matching_regex() {
+    let parser = RegexParser::new(r"^(?P<severity>\w+): (?P<message>.*)$").unwrap();
+
+    assert!(parser.parse("no severity here").is_err());
+}
+
+#[test]
+fn selector_picks_parser_by_glob() {
+    let selector = ParserSelector::new("/var/log/remote")
+        .with_rule("nginx/*.log", &ParserConfig::Rfc3164)
+        .unwrap();
+    let line = "<13>Feb  5 17:32:18 host app: text";
+
+    let nginx = selector.select(Path::new("/var/log/remote/nginx/access.log"));
+    let nested = selector.select(Path::new("/var/log/remote/nginx/old/access.log"));
+
+    assert!(nginx.parse(line).is_ok());
+    assert!(nested.parse(line).is_err());
+}
+
+#[test]
+fn decode_ndjson_batch_with_invalid_record() {
+    let body = concat!(
+        r#"{"timestamp":"2022-11-12T16:06:05+01:00","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"app:","source":"app","message":"first"}"#,
+        "\n\n",
+        r#"{"timestamp":"yesterday","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"app:","source":"app","message":"second"}"#,
+        "\n",
+        r#"{"host":"web-1"}"#,
+        "\n",
+    );
+
+    let timestamps = TimestampNormalizer::default();
+
+    let records = decode_log_batch(body.as_bytes(), &timestamps).expect("Cannot decode batch");
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].as_ref().unwrap().message, "first");
+    assert!(records[1].is_err());
+    assert!(records[2].is_err());
+}
+
+#[test]
+fn decode_json_array_batch() {
+    let body = r#"[
+        {"timestamp":"2022-11-12T16:06:05+01:00","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"app:","source":"app","message":"first"},
+        {"timestamp":"2022-11-12 16:06:06","host":"web-1","severity":"info","facility":"daemon","syslog_tag":"app:","source":"app","message":"second","redactions":["email"]},
+        {"message": 42}
+    ]"#;
+    let timestamps = TimestampNormalizer::new(Some("+02:00"), &[]).unwrap();
+
+    let records = decode_log_batch(body.as_bytes(), &timestamps).expect("Cannot decode batch");
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].as_ref().unwrap().message, "second");
+    assert_eq!(
+        records[1].as_ref().unwrap().timestamp,
+        "2022-11-12T16:06:06+02:00"
+    );
+    // Only the redaction processor marks entries as redacted
+    assert!(records[1].as_ref().unwrap().redactions.is_empty());
+    assert!(records[2].is_err());
+    assert!(decode_log_batch(b"[{", &timestamps).is_err());
+}
+
+#[test]
+fn successfully_parse_journal_json_entry() {
+    let line = r#"{"__REALTIME_TIMESTAMP":"1668265565250000","_HOSTNAME":"web-1","PRIORITY":"3","SYSLOG_FACILITY":"3","SYSLOG_IDENTIFIER":"sshd","_PID":"42","_SYSTEMD_UNIT":"ssh.service","MESSAGE":[99,97,102,233],"_BOOT_ID":"b7d1","TAG":["a","b"]}"#;
+
+    let log = JournalJsonParser
+        .parse(line)
+        .expect("Cannot parse journal entry");
+
+    assert_eq!(log.timestamp, "2022-11-12T15:06:05.250Z");
+    assert_eq!(log.host, "web-1");
+    assert_eq!(log.severity, "err");
+    assert_eq!(log.facility, "daemon");
+    assert_eq!(log.syslog_tag, "sshd[42]:");
+    assert_eq!(log.source, "ssh.service");
+    assert_eq!(log.app_name.as_deref(), Some("sshd"));
+    assert_eq!(log.message, "caf\u{FFFD}");
+    assert_eq!(log.attributes["_BOOT_ID"], "b7d1");
+    assert_eq!(log.attributes["TAG"], serde_json::json!(["a", "b"]));
+    assert!(!log.attributes.contains_key("MESSAGE"));
+}
+
+#[test]
+fn reject_journal_json_entry_without_timestamp() {
+    assert!(JournalJsonParser.parse(r#"{"MESSAGE":"started"}"#).is_err());
+    assert!(JournalJsonParser
+        .parse(r#"{"__REALTIME_TIMESTAMP":"1","PRIORITY":"9"}"#)
+        .is_err());
+}
+
+#[test]
+fn normalize_timestamps_in_source_timezone() {
+    let timestamps =
+        TimestampNormalizer::new(Some("Europe/Berlin"), &["%d.%m.%Y %H:%M:%S".into()]).unwrap();
+    let normalize = |timestamp: &str| timestamps.normalize(timestamp).unwrap();
+
+    assert_eq!(
+        normalize("2022-11-12T16:06:05+01:00"),
+        "2022-11-12T16:06:05+01:00"
+    );
+    assert_eq!(
+        normalize("10/Oct/2000:13:55:36 -0700"),
+        "2000-10-10T13:55:36-07:00"
+    );
+    assert_eq!(
+        normalize("2022-11-12 16:06:05"),
+        "2022-11-12T16:06:05+01:00"
+    );
+    assert_eq!(
+        normalize("2022-07-12T16:06:05.250"),
+        "2022-07-12T16:06:05.250+02:00"
+    );
+    assert_eq!(
+        normalize("12.11.2022 16:06:05"),
+        "2022-11-12T16:06:05+01:00"
+    );
+    assert_eq!(normalize("1668265565"), "2022-11-12T15:06:05+00:00");
+    assert_eq!(normalize("1668265565.25"), "2022-11-12T15:06:05.250+00:00");
+    assert_eq!(normalize("1668265565250"), "2022-11-12T15:06:05.250+00:00");
+
+    assert!(timestamps.normalize("yesterday").is_err());
+    // Numbers shorter than epoch seconds, e.g. a year or an id, aren't timestamps
+    assert!(timestamps.normalize("2024").is_err());
+    assert!(timestamps.normalize("12345678").is_err());
+    assert_eq!(normalize("100000000"), "1973-03-03T09:46:40+00:00");
+    assert!(TimestampNormalizer::new(Some("Mars/Olympus_Mons"), &[]).is_err());
+}
+
+#[test]
+fn infer_year_of_bsd_timestamp() {
+    let timestamps = TimestampNormalizer::new(Some("+05:30"), &[]).unwrap();
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(19_800).unwrap());
+    let yesterday = now - Duration::days(1);
+    let in_a_week = now + Duration::days(7);
+
+    let normalized = timestamps
+        .parse(&yesterday.format("%b %e %H:%M:%S").to_string())
+        .unwrap();
+    assert_eq!(normalized.year(), yesterday.year());
+    assert_eq!(normalized.offset().local_minus_utc(), 19_800);
+
+    let normalized = timestamps
+        .parse(&in_a_week.format("%b %e %H:%M:%S").to_string())
+        .unwrap();
+    assert_eq!(normalized.year(), in_a_week.year() - 1);
+}
+
+#[test]
+fn rfc3164_timestamp_is_read_in_source_timezone() {
+    let timestamps = TimestampNormalizer::new(Some("UTC"), &[]).unwrap();
+    let log = parse_line(
+        &Rfc3164Parser,
+        "<34>Oct 11 22:14:15 mymachine su: failed",
+        &timestamps,
+    )
+    .unwrap();
+
+    assert!(log.timestamp.ends_with("-10-11T22:14:15+00:00"));
+}
@@ -0,0 +1,198 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use application::prelude::FileSystem;
+use async_trait::async_trait;
+use infrastructure::prelude::{event_pipeline, PipelineStats};
+use notify::{
+    event::{CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode},
+    Event, EventKind,
+};
+
+#[tokio::test]
+async fn modifications_of_a_waiting_file_are_coalesced() {
+    let fs = RecordingFS::new(Duration::from_millis(50));
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    tokio::spawn(pipeline.run());
+
+    for _ in 0..10 {
+        sender.send(modify("app.log")).await;
+    }
+
+    wait_idle(&stats).await;
+
+    let stats = stats.snapshot();
+    assert!(fs.handled().len() <= 2, "Handled {:?}", fs.handled());
+    assert_eq!(stats.handled as usize, fs.handled().len());
+    assert_eq!(stats.handled + stats.coalesced, 10);
+    assert_eq!(stats.queued, 0);
+}
+
+#[tokio::test]
+async fn events_of_one_file_are_handled_in_order() {
+    let fs = RecordingFS::new(Duration::from_millis(10));
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    tokio::spawn(pipeline.run());
+
+    sender.send(create("app.log")).await;
+    sender.send(remove("app.log")).await;
+    sender.send(create("app.log")).await;
+    sender.send(modify("other.log")).await;
+    sender.send(remove("app.log")).await;
+
+    wait_idle(&stats).await;
+
+    let kinds: Vec<_> = fs
+        .handled()
+        .into_iter()
+        .filter(|(path, _)| path == Path::new("app.log"))
+        .map(|(_, kind)| kind)
+        .collect();
+
+    assert_eq!(kinds, ["create", "remove", "create", "remove"]);
+    assert_eq!(stats.snapshot().coalesced, 0);
+}
+
+#[tokio::test]
+async fn different_files_are_handled_concurrently() {
+    let fs = RecordingFS::new(Duration::from_millis(100));
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    tokio::spawn(pipeline.run());
+
+    let started = Instant::now();
+
+    for i in 0..16 {
+        sender.send(modify(&format!("{i}.log"))).await;
+    }
+
+    wait_idle(&stats).await;
+
+    assert_eq!(fs.handled().len(), 16);
+    assert!(fs.max_concurrent.load(Ordering::SeqCst) > 1);
+    assert!(started.elapsed() < Duration::from_millis(1600));
+}
+
+#[tokio::test]
+async fn renames_wait_for_the_previous_events() {
+    let fs = RecordingFS::new(Duration::from_millis(50));
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    tokio::spawn(pipeline.run());
+
+    sender.send(modify("app.log")).await;
+    sender.send(modify("other.log")).await;
+    sender.send(rename("app.log", "app.log.1")).await;
+    sender.send(modify("app.log")).await;
+
+    wait_idle(&stats).await;
+
+    let kinds: Vec<_> = fs.handled().into_iter().map(|(_, kind)| kind).collect();
+    assert_eq!(kinds[2..], ["rename", "modify"]);
+}
+
+#[tokio::test]
+async fn events_sent_to_a_stopped_pipeline_are_dropped() {
+    let fs = RecordingFS::new(Duration::ZERO);
+    let (sender, pipeline) = event_pipeline(fs.clone());
+    let stats = pipeline.stats();
+    drop(pipeline);
+
+    sender.send(modify("app.log")).await;
+
+    let stats = stats.snapshot();
+    assert_eq!(stats.dropped, 1);
+    assert_eq!(stats.queued, 0);
+    assert!(fs.handled().is_empty());
+}
+
+/// File system recording the handled events, each taking `delay`.
+struct RecordingFS {
+    delay: Duration,
+    handled: Mutex<Vec<(PathBuf, &'static str)>>,
+    running: AtomicUsize,
+    max_concurrent: AtomicUsize,
+}
+
+impl RecordingFS {
+    fn new(delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            delay,
+            handled: Mutex::new(Vec::new()),
+            running: AtomicUsize::new(0),
+            max_concurrent: AtomicUsize::new(0),
+        })
+    }
+
+    fn handled(&self) -> Vec<(PathBuf, &'static str)> {
+        self.handled.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl FileSystem for RecordingFS {
+    async fn handle_event(&self, event: Event) -> Result<()> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_concurrent.fetch_max(running, Ordering::SeqCst);
+
+        tokio::time::sleep(self.delay).await;
+
+        let kind = match event.kind {
+            EventKind::Create(_) => "create",
+            EventKind::Modify(ModifyKind::Name(_)) => "rename",
+            EventKind::Modify(_) => "modify",
+            EventKind::Remove(_) => "remove",
+            _ => "other",
+        };
+        self.handled
+            .lock()
+            .unwrap()
+            .push((event.paths[0].clone(), kind));
+
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn rescan(&self, _root: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+async fn wait_idle(stats: &PipelineStats) {
+    for _ in 0..250 {
+        if stats.snapshot().queued == 0 {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    panic!("Events still queued: {:?}", stats.snapshot());
+}
+
+fn create(path: &str) -> Event {
+    Event::new(EventKind::Create(CreateKind::File)).add_path(path.into())
+}
+
+fn modify(path: &str) -> Event {
+    Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Any))).add_path(path.into())
+}
+
+fn remove(path: &str) -> Event {
+    Event::new(EventKind::Remove(RemoveKind::File)).add_path(path.into())
+}
+
+fn rename(from: &str, to: &str) -> Event {
+    Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+        .add_path(from.into())
+        .add_path(to.into())
+}
@@ -0,0 +1,199 @@
+use std::{
+    net::UdpSocket as StdUdpSocket,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use application::prelude::{DiskLogEntryDto, LogRepository, RejectedLineDto};
+use async_trait::async_trait;
+use domain::prelude::{FileOffset, LogEntry, LogEntryFilter, ReposiotryResult};
+use infrastructure::prelude::{listen_udp, read_frame, TimestampNormalizer};
+use tokio::net::UdpSocket;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn udp_datagrams_are_stored() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_udp_address();
+    listen_udp(&address, log_repo.clone(), utc()).await.unwrap();
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let datagrams = [
+        "<34>1 2003-10-11T22:14:15.003Z web-1 su 42 ID47 [origin ip=\"10.0.0.1\"] failed",
+        "<13>Oct 11 22:14:15 web-2 nginx: started",
+    ];
+    for datagram in datagrams {
+        socket.send_to(datagram.as_bytes(), &address).await.unwrap();
+    }
+
+    let logs = log_repo.wait_for(2).await;
+
+    assert_eq!(logs[0].host, "web-1");
+    assert_eq!(logs[0].severity, "crit");
+    assert_eq!(logs[0].facility, "auth");
+    assert_eq!(logs[0].syslog_tag, "su[42]:");
+    assert_eq!(logs[0].msg_id.as_deref(), Some("ID47"));
+    assert_eq!(logs[0].attributes["origin"]["ip"], "10.0.0.1");
+    assert_eq!(logs[0].message, "failed");
+    assert_eq!(logs[0].client_identity, None);
+
+    assert_eq!(logs[1].host, "web-2");
+    assert_eq!(logs[1].severity, "notice");
+    assert_eq!(logs[1].facility, "user");
+    assert_eq!(logs[1].message, "started");
+    // The BSD timestamp is read in the timezone of the listener
+    assert!(logs[1].timestamp.ends_with("-10-11T22:14:15+00:00"));
+}
+
+#[tokio::test]
+async fn udp_datagram_without_host_gets_the_sender_address() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_udp_address();
+    listen_udp(&address, log_repo.clone(), utc()).await.unwrap();
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket
+        .send_to(
+            b"<14>1 2003-10-11T22:14:15.003Z - app - - - no host",
+            &address,
+        )
+        .await
+        .unwrap();
+
+    let logs = log_repo.wait_for(1).await;
+
+    assert_eq!(logs[0].host, "127.0.0.1");
+    assert_eq!(logs[0].message, "no host");
+}
+
+#[tokio::test]
+async fn invalid_udp_datagram_is_skipped() {
+    let log_repo = MemoryLogRepo::default();
+    let address = free_udp_address();
+    listen_udp(&address, log_repo.clone(), utc()).await.unwrap();
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket
+        .send_to(b"<34>1 yesterday web-1 su - - - invalid", &address)
+        .await
+        .unwrap();
+    socket
+        .send_to(b"<14>Oct 11 22:14:15 web-1 app: valid", &address)
+        .await
+        .unwrap();
+
+    let logs = log_repo.wait_for(1).await;
+
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].message, "valid");
+}
+
+#[tokio::test]
+async fn read_octet_counted_and_newline_frames() {
+    let stream = b"11 <14>1 first12 <14>1 second\n<14>third\r\n\n5 a\nb c<14>last";
+    let mut reader = &stream[..];
+
+    let mut frames = Vec::new();
+    while let Some(frame) = read_frame(&mut reader).await.unwrap() {
+        frames.push(String::from_utf8(frame).unwrap());
+    }
+
+    // The newline trailing an octet-counted frame reads as an empty frame
+    assert_eq!(
+        frames,
+        [
+            "<14>1 first",
+            "<14>1 second",
+            "",
+            "<14>third",
+            "",
+            "a\nb c",
+            "<14>last"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reject_oversize_frames() {
+    let limit = 1024 * 1024;
+
+    let counted = format!("{} <14>", limit + 1);
+    assert!(read_frame(&mut counted.as_bytes()).await.is_err());
+
+    let prefix = "1".repeat(limit + 2);
+    assert!(read_frame(&mut prefix.as_bytes()).await.is_err());
+
+    let line = format!("<14>{}\n", "a".repeat(limit));
+    assert!(read_frame(&mut line.as_bytes()).await.is_err());
+
+    let line = format!("<14>{}\n", "a".repeat(limit - 5));
+    let frame = read_frame(&mut line.as_bytes()).await.unwrap().unwrap();
+    assert_eq!(frame.len(), limit - 1);
+}
+
+fn utc() -> TimestampNormalizer {
+    TimestampNormalizer::new(Some("UTC"), &[]).unwrap()
+}
+
+fn free_udp_address() -> String {
+    let socket = StdUdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.local_addr().unwrap().to_string()
+}
+
+#[derive(Clone, Default)]
+struct MemoryLogRepo {
+    logs: Arc<Mutex<Vec<DiskLogEntryDto>>>,
+}
+
+impl MemoryLogRepo {
+    /// Waits until the listener stored the given number of entries.
+    async fn wait_for(&self, count: usize) -> Vec<DiskLogEntryDto> {
+        for _ in 0..100 {
+            if self.logs.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let logs = self.logs.lock().unwrap().clone();
+        assert_eq!(logs.len(), count, "Unexpected number of stored entries");
+        logs
+    }
+}
+
+#[async_trait]
+impl LogRepository for MemoryLogRepo {
+    async fn get_log_by_id(&self, _id: Uuid) -> ReposiotryResult<LogEntry> {
+        unimplemented!()
+    }
+
+    async fn get_logs_by_filter(&self, _filter: LogEntryFilter) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn get_all_logs(&self) -> ReposiotryResult<Vec<LogEntry>> {
+        unimplemented!()
+    }
+
+    async fn create_log(&self, dto: DiskLogEntryDto) -> ReposiotryResult<Uuid> {
+        self.logs.lock().unwrap().push(dto);
+        Ok(Uuid::new_v4())
+    }
+
+    async fn create_logs(&self, _dtos: Vec<DiskLogEntryDto>) -> ReposiotryResult<Vec<Uuid>> {
+        unimplemented!()
+    }
+
+    async fn create_logs_with_offset(
+        &self,
+        _dtos: Vec<DiskLogEntryDto>,
+        _rejected_line_dtos: Vec<RejectedLineDto>,
+        _offset: FileOffset,
+    ) -> ReposiotryResult<Vec<Uuid>> {
+        unimplemented!()
+    }
+
+    async fn delete_log(&self, _id: Uuid) -> ReposiotryResult<()> {
+        unimplemented!()
+    }
+}
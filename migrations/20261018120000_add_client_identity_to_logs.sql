@@ -0,0 +1 @@
+ALTER TABLE logs ADD COLUMN client_identity TEXT;
@@ -0,0 +1,7 @@
+ALTER TABLE logs
+    ADD COLUMN app_name TEXT,
+    ADD COLUMN proc_id TEXT,
+    ADD COLUMN msg_id TEXT,
+    ADD COLUMN attributes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE INDEX logs_attributes_idx ON logs USING GIN (attributes);
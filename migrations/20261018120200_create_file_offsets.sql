@@ -0,0 +1,9 @@
+CREATE TABLE file_offsets(
+    path TEXT NOT NULL,
+    PRIMARY KEY (path),
+    dev BIGINT NOT NULL,
+    ino BIGINT NOT NULL,
+    byte_offset BIGINT NOT NULL,
+    complete BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,12 @@
+CREATE TABLE rejected_lines(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    path TEXT NOT NULL,
+    byte_offset BIGINT NOT NULL,
+    raw TEXT NOT NULL,
+    error TEXT NOT NULL,
+    parser JSONB NOT NULL,
+    rejected_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX rejected_lines_path_idx ON rejected_lines (path);
@@ -0,0 +1,6 @@
+ALTER TABLE logs
+    ADD COLUMN trace_id TEXT,
+    ADD COLUMN span_id TEXT,
+    ADD COLUMN resource JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE INDEX logs_trace_id_idx ON logs (trace_id) WHERE trace_id IS NOT NULL;
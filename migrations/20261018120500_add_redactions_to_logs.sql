@@ -0,0 +1,4 @@
+ALTER TABLE logs
+    ADD COLUMN redactions TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX logs_redacted_idx ON logs (timestamp) WHERE cardinality(redactions) > 0;
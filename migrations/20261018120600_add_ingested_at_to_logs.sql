@@ -0,0 +1,2 @@
+ALTER TABLE logs
+    ADD COLUMN ingested_at TIMESTAMPTZ NOT NULL DEFAULT now();
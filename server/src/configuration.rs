@@ -1,5 +1,7 @@
 use anyhow::{anyhow, Result};
+use application::prelude::ProcessorConfig;
 use config::{File, FileFormat};
+use infrastructure::prelude::{ElasticFieldMap, SourceConfig};
 use secrecy::{ExposeSecret, Secret};
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::{
@@ -12,16 +14,47 @@ pub struct Settings {
     pub application: ApplicationSettings,
     pub database: DatabaseSettings,
     pub certificates: CertificateSettings,
-    pub cache: CacheSettings,
+    pub cache: Option<CacheSettings>,
+    pub syslog: Option<SyslogSettings>,
+    pub gelf: Option<GelfSettings>,
+    pub fluent: Option<FluentSettings>,
+    /// Document fields of the Elasticsearch `_bulk` endpoint, which is only served when set.
+    pub elastic: Option<ElasticFieldMap>,
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+    /// Processors run in order on the entries of every input before they're stored.
+    #[serde(default)]
+    pub processors: Vec<ProcessorConfig>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct ApplicationSettings {
-    pub folder_to_watch: String,
     pub host: String,
     pub port: u16,
     pub request_pool: u32,
     pub one_request_replenishment_time: u64,
+    /// Maximum size in bytes of a logs batch pushed to `POST /logs`.
+    #[serde(default = "default_ingest_body_limit")]
+    pub ingest_body_limit: usize,
+    /// Burst of requests to the ingest endpoints, which aren't limited like the UI.
+    #[serde(default = "default_ingest_request_pool")]
+    pub ingest_request_pool: u32,
+    #[serde(default = "default_ingest_replenishment_ms")]
+    pub ingest_one_request_replenishment_ms: u64,
+    /// Timezone of the timestamps without an offset pushed to `POST /logs`, local by default.
+    pub ingest_timezone: Option<String>,
+}
+
+fn default_ingest_body_limit() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_ingest_request_pool() -> u32 {
+    500
+}
+
+fn default_ingest_replenishment_ms() -> u64 {
+    10
 }
 
 #[derive(serde::Deserialize)]
@@ -87,3 +120,26 @@ pub struct CacheSettings {
     pub host: String,
     pub port: u16,
 }
+
+#[derive(serde::Deserialize)]
+pub struct SyslogSettings {
+    pub host: String,
+    pub udp_port: Option<u16>,
+    pub tcp_port: Option<u16>,
+    pub tls_port: Option<u16>,
+    /// Timezone of the timestamps without an offset, e.g. the BSD syslog ones, local by default.
+    pub timezone: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GelfSettings {
+    pub host: String,
+    pub udp_port: Option<u16>,
+    pub tcp_port: Option<u16>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FluentSettings {
+    pub host: String,
+    pub port: u16,
+}
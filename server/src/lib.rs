@@ -0,0 +1,4 @@
+pub mod configuration;
+pub mod middlewares;
+pub mod routes;
+pub mod startup;
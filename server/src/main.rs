@@ -1,16 +1,17 @@
-mod configuration;
-mod middlewares;
-mod routes;
-mod startup;
-
 use anyhow::Result;
+use application::prelude::{ProcessedLogRepo, ProcessorChain};
 use infrastructure::prelude::{
-    get_subscriber, init_subscriber, watch_dir, LinuxFS, PgLogRepo, SkyTableCache,
+    get_subscriber, init_subscriber, listen_fluent, listen_gelf_tcp, listen_gelf_udp, listen_tcp,
+    listen_udp, watch_dir, LinuxFS, PgLogRepo, SkyTableCache, Source, TimestampNormalizer,
 };
+use openssl::ssl::SslVerifyMode;
 use std::sync::Arc;
 
+use server::{
+    configuration,
+    startup::{run, setup_certificate_auth},
+};
 use sqlx::postgres::PgPoolOptions;
-use startup::run;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -20,16 +21,66 @@ async fn main() -> Result<()> {
     let config = configuration::get_configuration().unwrap();
 
     let connection_pool = PgPoolOptions::new().connect_lazy_with(config.database.with_db());
+    let processors = Arc::new(ProcessorChain::new(&config.processors)?);
+    let log_repo =
+        || ProcessedLogRepo::new(PgLogRepo::new(connection_pool.clone()), processors.clone());
+
+    let mut watchers = Vec::new();
+
+    for source_config in &config.sources {
+        let cache = config
+            .cache
+            .as_ref()
+            .map(|cache| SkyTableCache::new(&cache.host, cache.port));
+        let source = Source::new(source_config.clone())?;
+        let path = source.path().to_owned();
+        let poll = source.poll_config();
+        let file_system = Arc::new(LinuxFS::new(cache, log_repo(), source));
+
+        watchers.push(watch_dir(&path, poll, file_system)?);
+    }
+
+    if let Some(syslog) = &config.syslog {
+        let timestamps = TimestampNormalizer::new(syslog.timezone.as_deref(), &[])?;
+
+        if let Some(udp_port) = syslog.udp_port {
+            let address = format!("{}:{}", syslog.host, udp_port);
+            listen_udp(&address, log_repo(), timestamps.clone()).await?;
+        }
+
+        if let Some(tcp_port) = syslog.tcp_port {
+            let address = format!("{}:{}", syslog.host, tcp_port);
+            listen_tcp(&address, log_repo(), None, timestamps.clone()).await?;
+        }
+
+        if let Some(tls_port) = syslog.tls_port {
+            let address = format!("{}:{}", syslog.host, tls_port);
+            let mut builder = setup_certificate_auth(&config)?;
+            builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+            listen_tcp(&address, log_repo(), Some(builder.build()), timestamps).await?;
+        }
+    }
+
+    if let Some(gelf) = &config.gelf {
+        if let Some(udp_port) = gelf.udp_port {
+            let address = format!("{}:{}", gelf.host, udp_port);
+            listen_gelf_udp(&address, log_repo()).await?;
+        }
 
-    let cache = SkyTableCache::new(&config.cache.host, config.cache.port);
-    let log_repo = PgLogRepo::new(connection_pool.clone());
-    let file_system = Arc::new(LinuxFS::new(cache, log_repo));
+        if let Some(tcp_port) = gelf.tcp_port {
+            let address = format!("{}:{}", gelf.host, tcp_port);
+            listen_gelf_tcp(&address, log_repo()).await?;
+        }
+    }
 
-    let _watcher = watch_dir(&config.application.folder_to_watch, file_system)?;
+    if let Some(fluent) = &config.fluent {
+        let address = format!("{}:{}", fluent.host, fluent.port);
+        listen_fluent(&address, log_repo()).await?;
+    }
 
     let address = format!("{}:{}", config.application.host, config.application.port);
 
-    run(address, connection_pool, &config)?
+    run(address, connection_pool, &config, processors, watchers)?
         .await
         .expect("Failed to start HTTP server");
 
@@ -3,9 +3,10 @@ use actix_web::{
     body::EitherBody,
     dev::{self, Extensions, Service, ServiceRequest, ServiceResponse, Transform},
     rt::net::TcpStream,
-    Error, HttpResponse,
+    Error, HttpRequest, HttpResponse,
 };
 use futures_util::future::LocalBoxFuture;
+use infrastructure::prelude::common_name;
 use openssl::x509::X509;
 use std::{
     any::Any,
@@ -80,3 +81,8 @@ pub fn get_client_cert(connection: &dyn Any, data: &mut Extensions) {
         unreachable!("Socket should be TLS or plaintext");
     }
 }
+
+/// Common name of the client certificate the request was sent with.
+pub fn client_identity(request: &HttpRequest) -> Option<String> {
+    common_name(request.conn_data::<X509>()?)
+}
@@ -1,3 +1,3 @@
 mod auth;
 
-pub use auth::{get_client_cert, Auth, AuthMiddleware};
+pub use auth::{client_identity, get_client_cert, Auth, AuthMiddleware};
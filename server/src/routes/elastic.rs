@@ -0,0 +1,79 @@
+use std::time::Instant;
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use application::prelude::LogRepository;
+use infrastructure::prelude::{
+    bulk_response, cluster_info, decode_bulk, ElasticFieldMap, ELASTIC_PRODUCT_HEADER,
+};
+use tracing::{error, info};
+
+use super::IngestLogRepo;
+use crate::middlewares::client_identity;
+
+/// Answers the version check the Elasticsearch shippers make before sending anything.
+pub async fn elastic_info() -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header(ELASTIC_PRODUCT_HEADER)
+        .json(cluster_info())
+}
+
+/// Stores the documents of an Elasticsearch `_bulk` request, so that Filebeat, Vector
+/// or Logstash can ship logs with their Elasticsearch output.
+///
+/// The index of the path, `/{index}/_bulk`, is used by the actions which don't name one.
+/// As with Elasticsearch, the response lists the result of every action and the failed
+/// ones don't prevent the others from being stored.
+#[tracing::instrument(
+    name = "Receiving Elasticsearch bulk request",
+    skip(request, body, log_repo, fields)
+)]
+pub async fn elastic_bulk(
+    request: HttpRequest,
+    body: web::Bytes,
+    log_repo: web::Data<IngestLogRepo>,
+    fields: web::Data<ElasticFieldMap>,
+) -> impl Responder {
+    let started = Instant::now();
+    let index = request.match_info().get("index");
+
+    let items = match decode_bulk(&body, index, &fields) {
+        Ok(items) => items,
+        Err(e) => {
+            info!("Invalid bulk request. Reason: {:#}", e);
+            return HttpResponse::BadRequest()
+                .insert_header(ELASTIC_PRODUCT_HEADER)
+                .json(serde_json::json!({
+                    "error": { "type": "illegal_argument_exception", "reason": format!("{e:#}") },
+                    "status": 400,
+                }));
+        }
+    };
+
+    let client_identity = client_identity(&request);
+    let log_entries: Vec<_> = items
+        .iter()
+        .filter_map(|item| item.log_entry.as_ref().ok())
+        .map(|log_entry| {
+            let mut log_entry = log_entry.clone();
+            log_entry.client_identity = client_identity.clone();
+            log_entry
+        })
+        .collect();
+
+    if log_entries.len() < items.len() {
+        info!(
+            "{} actions of the bulk request failed",
+            items.len() - log_entries.len()
+        );
+    }
+
+    match log_repo.create_logs(log_entries).await {
+        Ok(ids) => HttpResponse::Ok()
+            .insert_header(ELASTIC_PRODUCT_HEADER)
+            .json(bulk_response(&items, &ids, started.elapsed())),
+        Err(e) => {
+            error!("Cannot store bulk request. Reason: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -1,12 +1,27 @@
 use std::collections::BTreeSet;
 
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use application::prelude::{BlacklistRepository, LogRepository};
 use domain::prelude::{LogEntry, LogEntryFilter, RepositoryError};
-use infrastructure::prelude::{PgBlkLstRepo, PgLogRepo};
+use infrastructure::prelude::{decode_log_batch, PgBlkLstRepo, PgLogRepo, TimestampNormalizer};
 use tracing::{error, info};
 use uuid::Uuid;
 
+use super::IngestLogRepo;
+use crate::middlewares::client_identity;
+
+#[derive(serde::Serialize, Debug)]
+pub struct IngestResponse {
+    ids: Vec<Uuid>,
+    errors: Vec<RecordError>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct RecordError {
+    index: usize,
+    error: String,
+}
+
 #[tracing::instrument(name = "Get all logs", skip(log_repo, blklst_repo))]
 pub async fn get_all_logs(
     log_repo: web::Data<PgLogRepo>,
@@ -48,10 +63,17 @@ pub async fn get_logs_by_filter(
     log_repo: web::Data<PgLogRepo>,
     blklst_repo: web::Data<PgBlkLstRepo>,
 ) -> impl Responder {
-    let mut logs = log_repo
-        .get_logs_by_filter(filters.into_inner())
-        .await
-        .expect("Cannot get filtred logs");
+    let mut logs = match log_repo.get_logs_by_filter(filters.into_inner()).await {
+        Ok(logs) => logs,
+        Err(RepositoryError::Parsing(e)) => {
+            info!("Invalid logs filter. Reason: {}", e);
+            return HttpResponse::BadRequest().body(e);
+        }
+        Err(e) => {
+            error!("Cannot get filtered logs. Reason: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
 
     let blacklisted = blklst_repo
         .get_all_entries()
@@ -64,3 +86,55 @@ pub async fn get_logs_by_filter(
 
     HttpResponse::Ok().json(logs)
 }
+
+/// Stores a batch of log entries pushed by a client which can't write into the watched
+/// folders, as a JSON array or newline delimited JSON.
+///
+/// The valid records are stored even if some of the others are invalid, the response lists
+/// the errors by the index of the record in the batch. The client identity of the entries
+/// is taken from the client certificate, the timestamps without an offset are read in the
+/// `ingest_timezone`.
+#[tracing::instrument(name = "Ingesting logs", skip(request, body, log_repo, timestamps))]
+pub async fn ingest_logs(
+    request: HttpRequest,
+    body: web::Bytes,
+    log_repo: web::Data<IngestLogRepo>,
+    timestamps: web::Data<TimestampNormalizer>,
+) -> impl Responder {
+    let records = match decode_log_batch(&body, &timestamps) {
+        Ok(records) => records,
+        Err(e) => {
+            info!("Invalid logs batch. Reason: {:#}", e);
+            return HttpResponse::BadRequest().body(format!("{e:#}"));
+        }
+    };
+
+    let client_identity = client_identity(&request);
+    let mut log_entries = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, record) in records.into_iter().enumerate() {
+        match record {
+            Ok(mut log_entry) => {
+                log_entry.client_identity = client_identity.clone();
+                log_entries.push(log_entry);
+            }
+            Err(e) => errors.push(RecordError {
+                index,
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+
+    if !errors.is_empty() {
+        info!("{} records of the logs batch are invalid", errors.len());
+    }
+
+    match log_repo.create_logs(log_entries).await {
+        Ok(ids) => HttpResponse::Ok().json(IngestResponse { ids, errors }),
+        Err(e) => {
+            error!("Cannot store logs batch. Reason: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -1,7 +1,21 @@
 mod blacklist;
+mod elastic;
 mod health_check;
 mod logs;
+mod otlp;
+mod rejected_lines;
+mod sources;
 
 pub use blacklist::*;
+pub use elastic::*;
 pub use health_check::*;
 pub use logs::*;
+pub use otlp::*;
+pub use rejected_lines::*;
+pub use sources::*;
+
+use application::prelude::ProcessedLogRepo;
+use infrastructure::prelude::PgLogRepo;
+
+/// Log repository of the routes receiving logs, running the processor chain on them.
+pub type IngestLogRepo = ProcessedLogRepo<PgLogRepo>;
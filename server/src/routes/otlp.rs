@@ -0,0 +1,51 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use application::prelude::LogRepository;
+use infrastructure::prelude::{decode_otlp_logs, OtlpEncoding};
+use tracing::{error, info};
+
+use super::IngestLogRepo;
+use crate::middlewares::client_identity;
+
+/// Receives an OpenTelemetry OTLP/HTTP logs export, encoded either as protobuf or JSON.
+///
+/// The response is encoded the same way as the request, as the OTLP exporters expect.
+#[tracing::instrument(name = "Receiving OTLP logs", skip(request, body, log_repo))]
+pub async fn export_otlp_logs(
+    request: HttpRequest,
+    body: web::Bytes,
+    log_repo: web::Data<IngestLogRepo>,
+) -> impl Responder {
+    let encoding = match request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(OtlpEncoding::from_content_type)
+    {
+        Some(encoding) => encoding,
+        None => return HttpResponse::UnsupportedMediaType().finish(),
+    };
+
+    let mut log_entries = match decode_otlp_logs(&body, encoding) {
+        Ok(log_entries) => log_entries,
+        Err(e) => {
+            info!("Invalid OTLP logs export. Reason: {:#}", e);
+            return HttpResponse::BadRequest().body(format!("{e:#}"));
+        }
+    };
+
+    let client_identity = client_identity(&request);
+
+    for log_entry in &mut log_entries {
+        log_entry.client_identity = client_identity.clone();
+    }
+
+    match log_repo.create_logs(log_entries).await {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(encoding.content_type())
+            .body(encoding.success_response()),
+        Err(e) => {
+            error!("Cannot store OTLP logs. Reason: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::Path;
+
+use actix_web::{web, HttpResponse, Responder};
+use application::prelude::{ProcessorChain, RejectedLineRepository};
+use domain::prelude::RepositoryError;
+use infrastructure::prelude::{
+    parse_line, ParserConfig, PgRejectedLineRepo, Source, TimestampNormalizer,
+};
+use tracing::{error, info};
+use uuid::Uuid;
+
+const DEFAULT_LIMIT: i64 = 100;
+/// Most lines returned at once, larger limits are lowered to it.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct RejectedLinesQuery {
+    path: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct PurgeQuery {
+    path: Option<String>,
+}
+
+#[tracing::instrument(name = "Retrieving rejected lines", skip(rejected_repo))]
+pub async fn get_rejected_lines(
+    query: web::Query<RejectedLinesQuery>,
+    rejected_repo: web::Data<PgRejectedLineRepo>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+
+    if limit < 1 {
+        return HttpResponse::BadRequest().body(format!("Limit {limit} is below 1"));
+    }
+
+    match rejected_repo
+        .get_rejected_lines(query.path, limit.min(MAX_LIMIT))
+        .await
+    {
+        Ok(lines) => HttpResponse::Ok().json(lines),
+        Err(e) => {
+            error!("Cannot get rejected lines. Reason: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Retrieving one rejected line", skip(rejected_repo))]
+pub async fn get_rejected_line_by_id(
+    line_id: web::Path<Uuid>,
+    rejected_repo: web::Data<PgRejectedLineRepo>,
+) -> impl Responder {
+    match rejected_repo.get_rejected_line_by_id(*line_id).await {
+        Ok(line) => HttpResponse::Ok().json(line),
+        Err(e) => rejected_line_error(*line_id, e),
+    }
+}
+
+/// Parses the rejected line again with the parser it was rejected by, e.g. after
+/// the line was fixed in the parser configuration or the parser itself.
+///
+/// The entry is completed by the source the line was read from, like the entries read
+/// from its files, then goes through the processor chain. The line is deleted if the chain
+/// drops the entry.
+#[tracing::instrument(
+    name = "Retrying rejected line",
+    skip(rejected_repo, processors, sources)
+)]
+pub async fn retry_rejected_line(
+    line_id: web::Path<Uuid>,
+    rejected_repo: web::Data<PgRejectedLineRepo>,
+    processors: web::Data<ProcessorChain>,
+    sources: web::Data<Vec<Source>>,
+) -> impl Responder {
+    let line = match rejected_repo.get_rejected_line_by_id(*line_id).await {
+        Ok(line) => line,
+        Err(e) => return rejected_line_error(*line_id, e),
+    };
+
+    let path = Path::new(&line.path);
+    let source = sources.iter().find(|source| source.matches(path));
+    let default_timestamps = TimestampNormalizer::default();
+    let timestamps = source.map_or(&default_timestamps, |source| source.timestamps());
+
+    let parsed = serde_json::from_value::<ParserConfig>(line.parser)
+        .map_err(anyhow::Error::from)
+        .and_then(|config| config.build())
+        .and_then(|parser| parse_line(parser.as_ref(), &line.raw, timestamps));
+
+    let log_entry = match parsed {
+        Ok(mut log_entry) => {
+            if let Some(source) = source {
+                source.apply(path, &mut log_entry);
+            }
+            log_entry
+        }
+        Err(e) => {
+            let mut reason = format!("{e:#}");
+            processors.redact_text(&mut reason);
+            info!(
+                "Rejected line '{}' still cannot be parsed. Reason: {}",
+                line.id, reason
+            );
+
+            if let Err(e) = rejected_repo
+                .update_rejected_line_error(line.id, &reason)
+                .await
+            {
+                error!("Cannot update rejected line '{}'. Reason: {:?}", line.id, e);
+            }
+
+            return HttpResponse::UnprocessableEntity().body(reason);
+        }
+    };
+
+    let log_entry = match processors.process(log_entry) {
+        Some(log_entry) => log_entry,
+        None => {
+            info!(
+                "Entry of rejected line '{}' is dropped by the processors",
+                line.id
+            );
+
+            return match rejected_repo.delete_rejected_line(line.id).await {
+                Ok(()) => HttpResponse::NoContent().finish(),
+                Err(e) => rejected_line_error(line.id, e),
+            };
+        }
+    };
+
+    match rejected_repo
+        .resolve_rejected_line(line.id, log_entry)
+        .await
+    {
+        Ok(log_id) => HttpResponse::Created()
+            .append_header(("Location", format!("/logs/{}", log_id)))
+            .finish(),
+        Err(e) => rejected_line_error(line.id, e),
+    }
+}
+
+#[tracing::instrument(name = "Deleting rejected line", skip(rejected_repo))]
+pub async fn delete_rejected_line(
+    line_id: web::Path<Uuid>,
+    rejected_repo: web::Data<PgRejectedLineRepo>,
+) -> impl Responder {
+    match rejected_repo.delete_rejected_line(*line_id).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => rejected_line_error(*line_id, e),
+    }
+}
+
+#[tracing::instrument(name = "Purging rejected lines", skip(rejected_repo))]
+pub async fn purge_rejected_lines(
+    query: web::Query<PurgeQuery>,
+    rejected_repo: web::Data<PgRejectedLineRepo>,
+) -> impl Responder {
+    match rejected_repo
+        .purge_rejected_lines(query.into_inner().path)
+        .await
+    {
+        Ok(purged) => HttpResponse::Ok().json(serde_json::json!({ "purged": purged })),
+        Err(e) => {
+            error!("Cannot purge rejected lines. Reason: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+fn rejected_line_error(line_id: Uuid, e: RepositoryError) -> HttpResponse {
+    match e {
+        RepositoryError::Database(sqlx::Error::RowNotFound) => {
+            info!("Rejected line with id '{}' doesn't exist", line_id);
+            HttpResponse::NotFound().finish()
+        }
+        e => {
+            error!(
+                "Cannot handle rejected line with id '{}'. Reason: {:?}",
+                line_id, e
+            );
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use actix_web::{web, HttpResponse};
+use infrastructure::prelude::{PipelineStatsSnapshot, WatchHandle};
+
+/// Event pipeline counters of a watched folder and its number of lines which weren't
+/// valid UTF-8.
+#[derive(serde::Serialize, Debug)]
+pub struct SourceStats<'a> {
+    path: &'a str,
+    #[serde(flatten)]
+    pipeline: PipelineStatsSnapshot,
+    invalid_utf8_lines: u64,
+}
+
+/// Counters of the watched folders, e.g. to tell whether events wait in the queue or are
+/// dropped, or whether files aren't in UTF-8.
+pub async fn get_source_stats(watchers: web::Data<Vec<WatchHandle>>) -> HttpResponse {
+    let stats: Vec<_> = watchers
+        .iter()
+        .map(|watcher| SourceStats {
+            path: watcher.path(),
+            pipeline: watcher.stats(),
+            invalid_utf8_lines: watcher.invalid_utf8_lines(),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(stats)
+}
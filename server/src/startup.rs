@@ -1,11 +1,16 @@
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{
     dev::Server,
+    guard::{self, GuardContext},
+    http::Method,
     web::{self, Data},
     App, HttpServer,
 };
 use anyhow::Result;
-use infrastructure::prelude::{PgBlkLstRepo, PgLogRepo};
+use application::prelude::{ProcessedLogRepo, ProcessorChain};
+use infrastructure::prelude::{
+    PgBlkLstRepo, PgLogRepo, PgRejectedLineRepo, Source, TimestampNormalizer, WatchHandle,
+};
 use openssl::{
     ssl::{
         SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslSessionCacheMode,
@@ -14,51 +19,131 @@ use openssl::{
     x509::{store::X509StoreBuilder, X509},
 };
 use sqlx::PgPool;
-use std::fs;
+use std::{fs, sync::Arc};
 use tracing_actix_web::TracingLogger;
 
 use crate::{
     configuration::Settings,
     middlewares::{get_client_cert, Auth},
     routes::{
-        add_to_blacklist, delete_entry_from_blacklist, get_all_logs, get_blacklist,
-        get_blacklist_entry_by_id, get_log_by_id, get_logs_by_filter, health_check,
+        add_to_blacklist, delete_entry_from_blacklist, delete_rejected_line, elastic_bulk,
+        elastic_info, export_otlp_logs, get_all_logs, get_blacklist, get_blacklist_entry_by_id,
+        get_log_by_id, get_logs_by_filter, get_rejected_line_by_id, get_rejected_lines,
+        get_source_stats, health_check, ingest_logs, purge_rejected_lines, retry_rejected_line,
     },
 };
 
-pub fn run(address: String, db_pool: PgPool, settings: &Settings) -> Result<Server> {
+/// Starts the HTTP server, which keeps the `watchers` of the sources running and serves
+/// their statistics.
+pub fn run(
+    address: String,
+    db_pool: PgPool,
+    settings: &Settings,
+    processors: Arc<ProcessorChain>,
+    watchers: Vec<WatchHandle>,
+) -> Result<Server> {
     let log_repo = Data::new(PgLogRepo::new(db_pool.clone()));
-    let blacklist = Data::new(PgBlkLstRepo::new(db_pool));
+    let ingest_log_repo = Data::new(ProcessedLogRepo::new(
+        PgLogRepo::new(db_pool.clone()),
+        processors.clone(),
+    ));
+    let processors = Data::from(processors);
+    let sources = Data::new(
+        settings
+            .sources
+            .iter()
+            .map(|config| Source::new(config.clone()))
+            .collect::<Result<Vec<_>>>()?,
+    );
+    let blacklist = Data::new(PgBlkLstRepo::new(db_pool.clone()));
+    let rejected_lines = Data::new(PgRejectedLineRepo::new(db_pool));
+    let watchers = Data::new(watchers);
 
     let ssl_builder = setup_certificate_auth(settings)?;
+    let ingest_body_limit = settings.application.ingest_body_limit;
+    let ingest_timestamps = Data::new(TimestampNormalizer::new(
+        settings.application.ingest_timezone.as_deref(),
+        &[],
+    )?);
+    let elastic_fields = settings.elastic.clone().map(Data::new);
 
     let governor_conf = GovernorConfigBuilder::default()
         .per_second(settings.application.one_request_replenishment_time)
         .burst_size(settings.application.request_pool)
         .finish()
         .unwrap();
+    let ingest_governor_conf = GovernorConfigBuilder::default()
+        .per_millisecond(settings.application.ingest_one_request_replenishment_ms)
+        .burst_size(settings.application.ingest_request_pool)
+        .finish()
+        .unwrap();
 
     let server = HttpServer::new(move || {
         App::new()
             .wrap(TracingLogger::default())
-            .wrap(Governor::new(&governor_conf))
             .wrap(Auth)
-            .route("/health_check", web::get().to(health_check))
-            .route("/logs/blacklist", web::post().to(add_to_blacklist))
-            .route("/logs/blacklist", web::get().to(get_blacklist))
-            .route(
-                "/logs/blacklist/{entry_id}",
-                web::delete().to(delete_entry_from_blacklist),
+            .service(
+                web::scope("")
+                    .guard(guard::fn_guard(is_ingest_request))
+                    .wrap(Governor::new(&ingest_governor_conf))
+                    .app_data(web::PayloadConfig::new(ingest_body_limit))
+                    .app_data(ingest_timestamps.clone())
+                    .route("/logs", web::post().to(ingest_logs))
+                    .route("/v1/logs", web::post().to(export_otlp_logs))
+                    .configure(|cfg| {
+                        if let Some(fields) = &elastic_fields {
+                            cfg.app_data(fields.clone())
+                                .route("/_bulk", web::post().to(elastic_bulk))
+                                .route("/{index}/_bulk", web::post().to(elastic_bulk));
+                        }
+                    }),
             )
-            .route(
-                "/logs/blacklist{entry_id}",
-                web::get().to(get_blacklist_entry_by_id),
+            .service(
+                web::scope("")
+                    .wrap(Governor::new(&governor_conf))
+                    .configure(|cfg| {
+                        if let Some(fields) = &elastic_fields {
+                            cfg.app_data(fields.clone())
+                                .route("/", web::get().to(elastic_info));
+                        }
+                    })
+                    .route("/health_check", web::get().to(health_check))
+                    .route("/sources/stats", web::get().to(get_source_stats))
+                    .route("/logs/blacklist", web::post().to(add_to_blacklist))
+                    .route("/logs/blacklist", web::get().to(get_blacklist))
+                    .route(
+                        "/logs/blacklist/{entry_id}",
+                        web::delete().to(delete_entry_from_blacklist),
+                    )
+                    .route(
+                        "/logs/blacklist{entry_id}",
+                        web::get().to(get_blacklist_entry_by_id),
+                    )
+                    .route("/logs/rejected", web::get().to(get_rejected_lines))
+                    .route("/logs/rejected", web::delete().to(purge_rejected_lines))
+                    .route(
+                        "/logs/rejected/{line_id}",
+                        web::get().to(get_rejected_line_by_id),
+                    )
+                    .route(
+                        "/logs/rejected/{line_id}",
+                        web::delete().to(delete_rejected_line),
+                    )
+                    .route(
+                        "/logs/rejected/{line_id}/retry",
+                        web::post().to(retry_rejected_line),
+                    )
+                    .route("/logs", web::get().to(get_all_logs))
+                    .route("/logs/filtered", web::get().to(get_logs_by_filter))
+                    .route("/logs/{log_id}", web::get().to(get_log_by_id)),
             )
-            .route("/logs", web::get().to(get_all_logs))
-            .route("/logs/filtered", web::get().to(get_logs_by_filter))
-            .route("/logs/{log_id}", web::get().to(get_log_by_id))
             .app_data(log_repo.clone())
+            .app_data(ingest_log_repo.clone())
+            .app_data(processors.clone())
+            .app_data(sources.clone())
             .app_data(blacklist.clone())
+            .app_data(rejected_lines.clone())
+            .app_data(watchers.clone())
     })
     .on_connect(get_client_cert)
     .bind_openssl(address, ssl_builder)?
@@ -67,7 +152,23 @@ pub fn run(address: String, db_pool: PgPool, settings: &Settings) -> Result<Serv
     Ok(server)
 }
 
-fn setup_certificate_auth(settings: &Settings) -> Result<SslAcceptorBuilder> {
+/// Tells whether the request is pushed by a log shipper, these are served by their own scope
+/// whose rate and body limits don't apply to the UI.
+fn is_ingest_request(ctx: &GuardContext) -> bool {
+    let path = ctx.head().uri.path();
+
+    ctx.head().method == Method::POST
+        && (matches!(path, "/logs" | "/v1/logs" | "/_bulk") || is_index_bulk(path))
+}
+
+/// Tells whether the path is the `_bulk` endpoint of an index, `/{index}/_bulk`.
+fn is_index_bulk(path: &str) -> bool {
+    path.strip_suffix("/_bulk")
+        .and_then(|index| index.strip_prefix('/'))
+        .is_some_and(|index| !index.is_empty() && !index.contains('/'))
+}
+
+pub fn setup_certificate_auth(settings: &Settings) -> Result<SslAcceptorBuilder> {
     let mut builder = SslAcceptor::mozilla_modern(SslMethod::tls())?;
     builder.set_private_key_file(&settings.certificates.server_key_path, SslFiletype::PEM)?;
     builder.set_certificate_chain_file(&settings.certificates.server_cert_path)?;
@@ -0,0 +1,258 @@
+use actix_web::{
+    http::StatusCode,
+    test,
+    web::{self, Data},
+    App,
+};
+use application::prelude::{LogRepository, ProcessorChain, RejectedLineDto};
+use chrono::Timelike;
+use domain::prelude::{FileOffset, RejectedLine};
+use infrastructure::prelude::{PgLogRepo, PgRejectedLineRepo, Source, SourceConfig};
+use serde_json::json;
+use server::routes::{
+    delete_rejected_line, get_rejected_line_by_id, get_rejected_lines, purge_rejected_lines,
+    retry_rejected_line,
+};
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use uuid::Uuid;
+
+const REMOTE_LOG: &str = "/var/log/remote/web-1/app.log";
+const LOCAL_LOG: &str = "/var/log/app.log";
+
+#[actix_web::test]
+async fn list_and_inspect_rejected_lines() {
+    let pool = spawn_db().await;
+    seed_rejected_lines(&pool).await;
+    let app = test::init_service(app(&pool)).await;
+
+    let request = test::TestRequest::get().uri("/logs/rejected").to_request();
+    let lines: Vec<RejectedLine> = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(lines.len(), 3);
+
+    let request = test::TestRequest::get()
+        .uri(&format!("/logs/rejected?path={LOCAL_LOG}&limit=1"))
+        .to_request();
+    let local: Vec<RejectedLine> = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(local.len(), 1);
+    assert_eq!(local[0].path, LOCAL_LOG);
+
+    let request = test::TestRequest::get()
+        .uri(&format!("/logs/rejected/{}", local[0].id))
+        .to_request();
+    let line: RejectedLine = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(line, local[0]);
+
+    let request = test::TestRequest::get()
+        .uri("/logs/rejected?limit=1000000000")
+        .to_request();
+    let lines: Vec<RejectedLine> = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(lines.len(), 3);
+
+    for limit in [0, -1] {
+        let request = test::TestRequest::get()
+            .uri(&format!("/logs/rejected?limit={limit}"))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    let request = test::TestRequest::get()
+        .uri(&format!("/logs/rejected/{}", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[actix_web::test]
+async fn retried_line_is_completed_by_its_source() {
+    let pool = spawn_db().await;
+    seed_rejected_lines(&pool).await;
+    let app = test::init_service(app(&pool)).await;
+    let line = rejected_line(&pool, "<13>Oct 11 22:14:15 - app: fixed").await;
+
+    let request = test::TestRequest::post()
+        .uri(&format!("/logs/rejected/{}/retry", line.id))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let location = response
+        .headers()
+        .get("Location")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let log_id = location.strip_prefix("/logs/").unwrap().parse().unwrap();
+    let log = PgLogRepo::new(pool.clone())
+        .get_log_by_id(log_id)
+        .await
+        .unwrap();
+
+    assert_eq!(log.host, "web-1");
+    assert_eq!(log.source, "remote");
+    assert_eq!(log.message, "fixed");
+    // The timestamp is read in the timezone of the source, +02:00
+    assert_eq!((log.timestamp.hour(), log.timestamp.minute()), (20, 14));
+
+    let request = test::TestRequest::get()
+        .uri(&format!("/logs/rejected/{}", line.id))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[actix_web::test]
+async fn line_still_invalid_is_kept_with_the_new_error() {
+    let pool = spawn_db().await;
+    seed_rejected_lines(&pool).await;
+    let app = test::init_service(app(&pool)).await;
+    let line = rejected_line(&pool, "not json").await;
+
+    let request = test::TestRequest::post()
+        .uri(&format!("/logs/rejected/{}/retry", line.id))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let request = test::TestRequest::get()
+        .uri(&format!("/logs/rejected/{}", line.id))
+        .to_request();
+    let retried: RejectedLine = test::call_and_read_body_json(&app, request).await;
+    assert_ne!(retried.error, "Stale error");
+}
+
+#[actix_web::test]
+async fn purge_rejected_lines_of_one_path() {
+    let pool = spawn_db().await;
+    seed_rejected_lines(&pool).await;
+    let app = test::init_service(app(&pool)).await;
+
+    let request = test::TestRequest::delete()
+        .uri(&format!("/logs/rejected?path={REMOTE_LOG}"))
+        .to_request();
+    let purged: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(purged, json!({ "purged": 2 }));
+
+    let request = test::TestRequest::get().uri("/logs/rejected").to_request();
+    let lines: Vec<RejectedLine> = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].path, LOCAL_LOG);
+
+    let request = test::TestRequest::delete()
+        .uri("/logs/rejected")
+        .to_request();
+    let purged: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(purged, json!({ "purged": 1 }));
+}
+
+fn app(
+    pool: &PgPool,
+) -> App<
+    impl actix_web::dev::ServiceFactory<
+        actix_web::dev::ServiceRequest,
+        Config = (),
+        Response = actix_web::dev::ServiceResponse,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let source: SourceConfig = serde_json::from_value(json!({
+        "path": "/var/log/remote",
+        "host_from_dir": true,
+        "source": "remote",
+        "timezone": "+02:00",
+    }))
+    .unwrap();
+
+    App::new()
+        .route("/logs/rejected", web::get().to(get_rejected_lines))
+        .route("/logs/rejected", web::delete().to(purge_rejected_lines))
+        .route(
+            "/logs/rejected/{line_id}",
+            web::get().to(get_rejected_line_by_id),
+        )
+        .route(
+            "/logs/rejected/{line_id}",
+            web::delete().to(delete_rejected_line),
+        )
+        .route(
+            "/logs/rejected/{line_id}/retry",
+            web::post().to(retry_rejected_line),
+        )
+        .app_data(Data::new(PgRejectedLineRepo::new(pool.clone())))
+        .app_data(Data::new(ProcessorChain::default()))
+        .app_data(Data::new(vec![Source::new(source).unwrap()]))
+}
+
+/// Stores two rejected lines of a remote host and one of the local host.
+async fn seed_rejected_lines(pool: &PgPool) {
+    let rejected_lines = [
+        (REMOTE_LOG, "<13>Oct 11 22:14:15 - app: fixed", "rfc3164"),
+        (REMOTE_LOG, "not json", "rsyslog_json"),
+        (LOCAL_LOG, "also not json", "rsyslog_json"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (path, raw, format))| RejectedLineDto {
+        path: path.into(),
+        byte_offset: i as i64 * 100,
+        raw: raw.into(),
+        error: "Stale error".into(),
+        parser: json!({ "format": format }),
+    })
+    .collect();
+    let offset = FileOffset {
+        path: REMOTE_LOG.into(),
+        dev: 1,
+        ino: 2,
+        byte_offset: 300,
+        complete: false,
+    };
+
+    PgLogRepo::new(pool.clone())
+        .create_logs_with_offset(Vec::new(), rejected_lines, offset)
+        .await
+        .expect("Cannot store rejected lines");
+}
+
+async fn rejected_line(pool: &PgPool, raw: &str) -> RejectedLine {
+    sqlx::query_as::<_, RejectedLine>("SELECT * FROM rejected_lines WHERE raw = $1")
+        .bind(raw)
+        .fetch_one(pool)
+        .await
+        .expect("Cannot find rejected line")
+}
+
+/// Creates a fresh database on the Postgres instance from `DATABASE_URL`.
+async fn spawn_db() -> PgPool {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL has to be set");
+    let database_name = Uuid::new_v4().to_string();
+
+    let mut connection = PgConnection::connect(&url)
+        .await
+        .expect("Failed to connect to Postgres instance");
+    connection
+        .execute(format!(r#"CREATE DATABASE "{database_name}";"#).as_str())
+        .await
+        .expect("Failed to create database");
+
+    let (server_url, _) = url.rsplit_once('/').expect("Invalid DATABASE_URL");
+    let pool = PgPool::connect(&format!("{server_url}/{database_name}"))
+        .await
+        .expect("Failed to connect to Postgres");
+
+    sqlx::migrate!("../migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    pool
+}
@@ -20,6 +20,7 @@ async fn successfully_create_log_entry_in_database() {
         source: "Unit test".into(),
         syslog_tag: "Sample tag".into(),
         timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
     };
 
     let res = log_repo.create_log(log_dto).await;
@@ -40,6 +41,7 @@ async fn successfully_get_log_entry_from_database() -> Result<()> {
         source: "Unit test".into(),
         syslog_tag: "Sample tag".into(),
         timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
     };
 
     let log_entry_id = log_repo
@@ -66,6 +68,7 @@ async fn successfully_get_all_log_entries_from_database() -> Result<()> {
         source: "Unit test".into(),
         syslog_tag: "Sample tag".into(),
         timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
     };
 
     let mut log_dto_2 = log_dto_1.clone();
@@ -112,6 +115,7 @@ async fn successfully_delete_log_entry_from_database() -> Result<()> {
         source: "Unit test".into(),
         syslog_tag: "Sample tag".into(),
         timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
     };
 
     let id = log_repo.create_log(log_dto_1).await?;